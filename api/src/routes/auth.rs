@@ -1,6 +1,8 @@
+use crate::middleware::jwt_validator;
 use crate::models::{AuthResponse, Claims, LoginRequest, RegisterRequest, User, UserResponse};
 use crate::utils::{create_jwt, ApiError};
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder};
+use actix_web_httpauth::middleware::HttpAuthentication;
 use bcrypt::{hash, verify, DEFAULT_COST};
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -123,6 +125,11 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .route("/register", web::post().to(register))
             .route("/login", web::post().to(login))
             .route("/logout", web::post().to(logout))
-            .route("/me", web::get().to(me)),
+            // The auth scope is public, so /me checks the token itself
+            .service(
+                web::resource("/me")
+                    .wrap(HttpAuthentication::bearer(jwt_validator))
+                    .route(web::get().to(me)),
+            ),
     );
 }
@@ -1,16 +1,70 @@
 use clap::{Parser, Subcommand};
+use clap_complete::engine::{ArgValueCandidates, ArgValueCompleter};
+
+use crate::commands::completions::{self, Shell};
 
 #[derive(Parser)]
 #[command(name = "animaforge")]
 #[command(about = "AI-Powered Animation Creation Tool", long_about = None)]
 #[command(version)]
 pub struct Cli {
+    /// Config profile to use instead of ANIMAFORGE_PROFILE or the configured one
+    #[arg(long, global = true, value_name = "NAME", add = ArgValueCandidates::new(completions::profiles))]
+    pub profile: Option<String>,
+
+    /// Print the result as JSON on stdout; logs go to stderr
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Print only errors, and the result with --json
+    #[arg(long, global = true)]
+    pub quiet: bool,
+
+    /// Disable colored output (also set by NO_COLOR)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
+    /// Set up the AI backend, tools and output settings
+    Init {
+        /// AI backend (ollama, gemini, claude)
+        #[arg(long)]
+        backend: Option<String>,
+
+        /// Model name
+        #[arg(long, add = ArgValueCandidates::new(completions::models))]
+        model: Option<String>,
+
+        /// API key for cloud backends
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// Ollama server URL
+        #[arg(long, value_name = "URL")]
+        ollama_endpoint: Option<String>,
+
+        /// Directory for generated animations
+        #[arg(long, value_name = "DIR")]
+        output_dir: Option<String>,
+
+        /// Default render quality (low, medium, high)
+        #[arg(long, add = ArgValueCandidates::new(completions::qualities))]
+        quality: Option<String>,
+
+        /// Don't ask; use the flags and defaults for everything else
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Skip the test generation at the end
+        #[arg(long)]
+        skip_test: bool,
+    },
+
     /// Create animation from text prompt
     Create {
         /// Text description of the animation to create
@@ -27,16 +81,186 @@ pub enum Commands {
 
     /// Render animation from code file
     Render {
+        /// Path to animation code file, directory or glob pattern
+        file: String,
+
+        /// Rendering quality: low, medium, high (default: the configured quality)
+        #[arg(short, long, add = ArgValueCandidates::new(completions::qualities))]
+        quality: Option<String>,
+
+        /// Render target from the config, e.g. shorts for [targets.shorts]
+        #[arg(long, value_name = "NAME", add = ArgValueCandidates::new(completions::targets))]
+        target: Option<String>,
+
+        /// Output video file path (output directory when rendering a batch)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Number of files to render in parallel in batch mode
+        #[arg(short, long, default_value = "1")]
+        jobs: usize,
+
+        /// Kill a render that runs longer than this many seconds
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+
+        /// CPU time limit for the manim process in seconds
+        #[arg(long, value_name = "SECS")]
+        cpu_limit: Option<u64>,
+
+        /// Memory limit for the manim process in megabytes
+        #[arg(long, value_name = "MB")]
+        memory_limit: Option<u64>,
+
+        /// Render in a sandbox without network and with a read-only filesystem
+        #[arg(long, conflicts_with = "no_sandbox")]
+        sandbox: bool,
+
+        /// Never sandbox, even if the config asks for it
+        #[arg(long)]
+        no_sandbox: bool,
+
+        /// Timestamp in seconds of the thumbnail frame
+        #[arg(long, value_name = "SECS")]
+        thumbnail_at: Option<f64>,
+
+        /// Also generate a short preview GIF
+        #[arg(long)]
+        gif: bool,
+
+        /// Skip thumbnail generation
+        #[arg(long)]
+        no_thumbnail: bool,
+
+        /// Render every scene in the file and join them into one video
+        #[arg(long)]
+        stitch: bool,
+
+        /// Scene order for --stitch (default: declaration order)
+        #[arg(
+            long,
+            value_delimiter = ',',
+            requires = "stitch",
+            add = ArgValueCompleter::new(completions::scenes)
+        )]
+        order: Vec<String>,
+
+        /// Crossfade between stitched scenes, in seconds
+        #[arg(long, value_name = "SECS", requires = "stitch")]
+        crossfade: Option<f64>,
+
+        /// Audio file to add to the video (e.g. a voice-over)
+        #[arg(long, value_name = "FILE")]
+        audio: Option<String>,
+
+        /// Delay the audio by this many seconds; negative values skip into it
+        #[arg(long, value_name = "SECS", default_value = "0", allow_hyphen_values = true, requires = "audio")]
+        audio_offset: f64,
+
+        /// Audio volume multiplier
+        #[arg(long, value_name = "FACTOR", default_value = "1.0", requires = "audio")]
+        audio_volume: f64,
+
+        /// Subtitle file (.srt or .vtt) to add as a caption track
+        #[arg(long, value_name = "FILE")]
+        subtitles: Option<String>,
+
+        /// Burn the subtitles into the picture instead of adding a track
+        #[arg(long, requires = "subtitles")]
+        burn_subtitles: bool,
+
+        /// Check the output for blank or static frames, cropped content and length
+        #[arg(long)]
+        check: bool,
+
+        /// Fail the check if the video is shorter than this
+        #[arg(long, value_name = "SECS", requires = "check")]
+        min_duration: Option<f64>,
+
+        /// Fail the check if the video is longer than this
+        #[arg(long, value_name = "SECS", requires = "check")]
+        max_duration: Option<f64>,
+    },
+
+    /// Render a quick preview (still frames or a low-res cut)
+    Preview {
         /// Path to animation code file
         file: String,
 
+        /// Capture frames at these timestamps in seconds (e.g. --at 1.5,4)
+        #[arg(long, value_delimiter = ',')]
+        at: Vec<f64>,
+
+        /// Render a quick low-quality video at this frame rate
+        #[arg(long)]
+        fps: Option<u32>,
+
+        /// Preview resolution (480p, 720p, 1080p)
+        #[arg(long, default_value = "480p")]
+        resolution: String,
+
+        /// Output directory (default: next to the input file)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+
+    /// Check an animation file with the engine's validator
+    Validate {
+        /// Path to animation code file
+        file: String,
+    },
+
+    /// Compare rendered frames of every scene with stored golden PNGs
+    Test {
+        /// Animation files, directories or glob patterns
+        #[arg(default_value = ".")]
+        inputs: Vec<String>,
+
+        /// Check frames at these timestamps in seconds (default: the last frame)
+        #[arg(long, value_delimiter = ',')]
+        at: Vec<f64>,
+
         /// Rendering quality (low, medium, high)
-        #[arg(short, long, default_value = "medium")]
+        #[arg(short, long, default_value = "low", add = ArgValueCandidates::new(completions::qualities))]
         quality: String,
 
-        /// Output video file path
+        /// Accept the rendered frames as the new goldens
+        #[arg(long)]
+        update: bool,
+
+        /// Percentage of pixels that may differ before a frame fails
+        #[arg(long, value_name = "PERCENT", default_value = "0.1")]
+        tolerance: f64,
+
+        /// Per-pixel color difference (0-1) below which pixels count as equal
+        #[arg(long, default_value = "0.1")]
+        threshold: f64,
+
+        /// Golden frame directory (default: goldens/ next to each file)
+        #[arg(long, value_name = "DIR")]
+        goldens: Option<String>,
+
+        /// Directory for the rendered frames and diff images of failures
+        #[arg(long, value_name = "DIR", default_value = "test-results")]
+        results: String,
+    },
+
+    /// Start an animation from a built-in template
+    Template {
+        /// Template name (omit to list the available templates)
+        name: Option<String>,
+
+        /// Template parameter, e.g. --set title="Hello" (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        values: Vec<String>,
+
+        /// Output file path (default: <output_dir>/<name>.py)
         #[arg(short, long)]
         output: Option<String>,
+
+        /// Render the animation after writing it
+        #[arg(short, long)]
+        render: bool,
     },
 
     /// Manage CLI configuration
@@ -45,6 +269,12 @@ pub enum Commands {
         action: ConfigAction,
     },
 
+    /// Manage local Ollama models
+    Models {
+        #[command(subcommand)]
+        action: ModelsAction,
+    },
+
     /// Publish animation to marketplace
     Publish {
         /// Path to animation file or directory
@@ -60,32 +290,137 @@ pub enum Commands {
         #[arg(short, long, default_value = "10")]
         limit: usize,
     },
+
+    /// Check the backend, tools, config and marketplace for problems
+    Doctor,
+
+    /// Print a shell completion script, e.g. `source <(animaforge completions bash)`
+    Completions {
+        /// Shell to complete in
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Print the man page, or write one per command with --out-dir
+    Man {
+        /// Directory for the pages, e.g. /usr/local/share/man/man1
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 pub enum ConfigAction {
-    /// Set a configuration value
+    /// Set a configuration value, e.g. `set thumbnails.width 640`
     Set {
-        /// Configuration key (backend, model, api_key, output_dir)
-        #[arg(long)]
-        backend: Option<String>,
+        /// Configuration key; nested keys are joined with dots
+        #[arg(add = ArgValueCandidates::new(completions::config_keys))]
+        key: String,
 
-        #[arg(long)]
-        model: Option<String>,
-
-        #[arg(long)]
-        api_key: Option<String>,
+        /// New value, checked against the key's type
+        value: String,
+    },
 
-        #[arg(long)]
-        output_dir: Option<String>,
+    /// Remove a configuration value so its default applies
+    Unset {
+        /// Configuration key
+        #[arg(add = ArgValueCandidates::new(completions::config_keys))]
+        key: String,
     },
 
     /// Get a configuration value
     Get {
         /// Configuration key to retrieve
+        #[arg(add = ArgValueCandidates::new(completions::config_keys))]
         key: Option<String>,
     },
 
     /// List all configuration values
+    List {
+        /// Show where each value came from (default, file, env var or flag)
+        #[arg(long)]
+        show_origin: bool,
+    },
+
+    /// Open the config file in $EDITOR and validate it on save
+    Edit,
+
+    /// Encrypt the stored API key and tokens with a passphrase
+    Encrypt,
+
+    /// Store the API key and tokens unencrypted again
+    Decrypt,
+
+    /// Manage named profiles that override config values
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// List profiles and show which one is active
+    List,
+
+    /// Create a profile; set its values with `--profile NAME config set`
+    Create {
+        /// Profile name
+        name: String,
+
+        /// Start with a copy of this profile's overrides
+        #[arg(long, value_name = "PROFILE", add = ArgValueCandidates::new(completions::profiles))]
+        from: Option<String>,
+    },
+
+    /// Use a profile by default ("default" for none)
+    Use {
+        /// Profile name
+        #[arg(add = ArgValueCandidates::new(completions::profiles))]
+        name: String,
+    },
+
+    /// Delete a profile
+    Delete {
+        /// Profile name
+        #[arg(add = ArgValueCandidates::new(completions::profiles))]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ModelsAction {
+    /// List installed models with their size, family and quantization
     List,
+
+    /// Download a model from the Ollama library
+    Pull {
+        /// Model name, e.g. llama3 or qwen2.5-coder:7b
+        name: String,
+    },
+
+    /// Show a model's details and default parameters
+    Show {
+        /// Model name (default: the configured model)
+        #[arg(add = ArgValueCandidates::new(completions::models))]
+        name: Option<String>,
+    },
+
+    /// Generate with this model from now on
+    Use {
+        /// Name of an installed model
+        #[arg(add = ArgValueCandidates::new(completions::models))]
+        name: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn global_flags_do_not_clash() {
+        Cli::command().debug_assert();
+    }
 }
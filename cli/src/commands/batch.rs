@@ -0,0 +1,315 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::commands::render::{
+    check_manim, determine_output_path, extract_scene_name, render_with_manim, QualitySettings,
+    RenderOptions, Termination,
+};
+use crate::manim::target as manim_target;
+use crate::media;
+use crate::utils::output::{self, ErrorCode};
+use crate::utils::process;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum JobStatus {
+    Success,
+    Failed,
+    /// Killed for exceeding a limit or cancelled
+    Terminated,
+}
+
+/// Outcome of rendering a single file in a batch
+#[derive(Debug, Serialize)]
+struct JobResult {
+    file: String,
+    scene: Option<String>,
+    status: JobStatus,
+    /// Wall-clock time the render took
+    render_secs: f64,
+    output: Option<String>,
+    thumbnail: Option<String>,
+    /// Length of the rendered video in seconds
+    video_duration: Option<f64>,
+    error: Option<String>,
+    /// Video details, thumbnails or the sidecar that could not be made
+    warnings: Vec<String>,
+}
+
+/// Whether a render input names several files (a directory or glob pattern)
+pub fn is_batch_input(input: &str) -> bool {
+    Path::new(input).is_dir() || input.contains(['*', '?', '['])
+}
+
+/// Expand a directory or glob pattern into the Python files it matches
+pub fn resolve_inputs(input: &str) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = if Path::new(input).is_dir() {
+        fs::read_dir(input)
+            .context("Failed to read input directory")?
+            .flatten()
+            .map(|entry| entry.path())
+            .collect()
+    } else {
+        glob::glob(input)
+            .context("Invalid glob pattern")?
+            .flatten()
+            .collect()
+    };
+
+    files.retain(|path| path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("py"));
+    files.sort();
+
+    if files.is_empty() {
+        anyhow::bail!("No Python animation files found matching: {}", input);
+    }
+
+    Ok(files)
+}
+
+pub fn execute(inputs: Vec<PathBuf>, options: &RenderOptions) -> Result<()> {
+    let quality = &options.quality;
+    let quality_settings = QualitySettings::from_name(quality)?;
+    let args = options.manim_args(&quality_settings)?;
+    let jobs = options.jobs.clamp(1, inputs.len());
+
+    println!("{}", "Rendering batch...".bright_green().bold());
+    println!("{} {}", "Files:".bright_yellow(), inputs.len().to_string().bright_cyan());
+    println!(
+        "{} {} ({})",
+        "Quality:".bright_yellow(),
+        quality.bright_cyan(),
+        quality_settings.resolution
+    );
+    if let Some((name, target)) = &options.target {
+        println!(
+            "{} {} ({})",
+            "Target:".bright_yellow(),
+            name.bright_cyan(),
+            manim_target::summary(target)
+        );
+    }
+    println!("{} {}\n", "Jobs:".bright_yellow(), jobs.to_string().bright_cyan());
+
+    // Fail once up front rather than once per file
+    check_manim()?;
+
+    let multi = MultiProgress::new();
+    if output::is_scripted() {
+        multi.set_draw_target(indicatif::ProgressDrawTarget::hidden());
+    }
+    let style = ProgressStyle::default_bar()
+        .template("{spinner:.green} {prefix:.bold} [{bar:30.cyan/blue}] {pos}% {wide_msg}")
+        .unwrap()
+        .progress_chars("#>-");
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<(usize, JobResult)>> = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            scope.spawn(|| loop {
+                // Don't start new jobs after Ctrl-C
+                if process::is_cancelled() {
+                    break;
+                }
+
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let Some(input) = inputs.get(index) else {
+                    break;
+                };
+
+                let pb = multi.add(ProgressBar::new(100));
+                pb.set_style(style.clone());
+                pb.set_prefix(display_name(input));
+
+                let result = render_job(input, &args, options, &pb);
+
+                match result.status {
+                    JobStatus::Success => {
+                        pb.finish_with_message(format!("{} done", "✓".green()))
+                    }
+                    JobStatus::Failed | JobStatus::Terminated => pb.abandon_with_message(format!(
+                        "{} {}",
+                        "✗".red(),
+                        result.error.as_deref().unwrap_or("failed")
+                    )),
+                }
+
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    let results: Vec<JobResult> = results.into_iter().map(|(_, result)| result).collect();
+
+    output::set_result(serde_json::json!({ "renders": &results }));
+    print_summary(&results);
+
+    if process::is_cancelled() {
+        return Err(Termination::Cancelled.into());
+    }
+
+    let failed = results
+        .iter()
+        .filter(|r| !matches!(r.status, JobStatus::Success))
+        .count();
+
+    if failed > 0 {
+        return Err(output::failure(
+            ErrorCode::RenderFailed,
+            format!("{} of {} renders failed", failed, results.len()),
+        ));
+    }
+
+    Ok(())
+}
+
+fn render_job(
+    input: &Path,
+    args: &[String],
+    options: &RenderOptions,
+    pb: &ProgressBar,
+) -> JobResult {
+    let start = Instant::now();
+    let mut result = JobResult {
+        file: input.display().to_string(),
+        scene: None,
+        status: JobStatus::Failed,
+        render_secs: 0.0,
+        output: None,
+        thumbnail: None,
+        video_duration: None,
+        error: None,
+        warnings: Vec::new(),
+    };
+
+    let outcome = (|| -> Result<PathBuf> {
+        let scene_name = extract_scene_name(input)?;
+        result.scene = Some(scene_name.clone());
+
+        let output_path = match options.output.as_deref() {
+            Some(dir) => {
+                let mut path = PathBuf::from(dir).join(input.file_name().unwrap());
+                path.set_extension(options.extension());
+                path
+            }
+            None => determine_output_path(input, None, options.extension())?,
+        };
+
+        render_with_manim(input, &scene_name, &output_path, args, &options.limits, pb)?;
+
+        // Thumbnail problems are not worth failing a batch job over
+        pb.set_message("Inspecting video and making thumbnails...");
+        match media::process_render(input, &output_path, &options.thumbnails) {
+            Ok((artifacts, warnings)) => {
+                result.thumbnail = artifacts.thumbnail;
+                result.video_duration = artifacts.media.map(|m| m.duration_secs);
+                result.warnings = warnings;
+            }
+            Err(e) => result.warnings.push(format!("Could not record the render: {:#}", e)),
+        }
+
+        Ok(output_path)
+    })();
+
+    match outcome {
+        Ok(path) => {
+            result.status = JobStatus::Success;
+            result.output = Some(path.display().to_string());
+        }
+        Err(e) => {
+            if e.downcast_ref::<Termination>().is_some() {
+                result.status = JobStatus::Terminated;
+            }
+            result.error = Some(e.to_string());
+        }
+    }
+
+    result.render_secs = start.elapsed().as_secs_f64();
+    result
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+fn print_summary(results: &[JobResult]) {
+    println!("\n{}", "Batch Summary:".bright_green().bold());
+    println!("{}", "═".repeat(80).bright_black());
+    println!(
+        "{:<24} {:<20} {:<8} {:>8}  {}",
+        "File".bold(),
+        "Scene".bold(),
+        "Status".bold(),
+        "Time".bold(),
+        "Output".bold()
+    );
+
+    for result in results {
+        let status = match result.status {
+            JobStatus::Success => "ok".green(),
+            JobStatus::Failed => "failed".red(),
+            JobStatus::Terminated => "killed".red(),
+        };
+
+        println!(
+            "{:<24} {:<20} {:<8} {:>7.1}s  {}",
+            display_name(Path::new(&result.file)),
+            result.scene.as_deref().unwrap_or("-"),
+            status,
+            result.render_secs,
+            result
+                .output
+                .as_deref()
+                .or(result.error.as_deref())
+                .unwrap_or("-")
+        );
+    }
+
+    println!("{}", "═".repeat(80).bright_black());
+
+    for result in results {
+        for warning in &result.warnings {
+            println!(
+                "{} {}: {}",
+                "⚠".yellow(),
+                display_name(Path::new(&result.file)),
+                warning.yellow()
+            );
+        }
+    }
+
+    let succeeded = results
+        .iter()
+        .filter(|r| matches!(r.status, JobStatus::Success))
+        .count();
+    println!(
+        "{} {}/{} rendered successfully",
+        "→".bright_blue(),
+        succeeded,
+        results.len()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_batch_input() {
+        assert!(is_batch_input("scenes/*.py"));
+        assert!(is_batch_input("scene_?.py"));
+        assert!(!is_batch_input("scene.py"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Shell completion. The script printed by `completions` calls back into
+//! animaforge with ANIMAFORGE_COMPLETE set, so completions always match the
+//! installed binary, and values like profiles and models come from the
+//! config and Ollama as they are now.
+
+use anyhow::Result;
+use clap::ValueEnum;
+use clap_complete::engine::CompletionCandidate;
+use clap_complete::env::{Bash, EnvCompleter, Fish, Powershell, Zsh};
+use std::ffi::OsStr;
+use std::path::PathBuf;
+use std::time::Duration;
+use toml::{Table, Value};
+
+use crate::commands::render::{extract_scene_names, QualitySettings};
+use crate::config::{layers, Config, DEFAULT_OLLAMA_ENDPOINT, DEFAULT_PROFILE};
+use crate::llm::ollama::OllamaClient;
+
+/// Set by the completion script when asking for completions
+pub const COMPLETE_VAR: &str = "ANIMAFORGE_COMPLETE";
+
+/// How long to wait for Ollama before completing without models
+const OLLAMA_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+pub fn execute(shell: Shell) -> Result<()> {
+    let completer: &dyn EnvCompleter = match shell {
+        Shell::Bash => &Bash,
+        Shell::Zsh => &Zsh,
+        Shell::Fish => &Fish,
+        Shell::Powershell => &Powershell,
+    };
+
+    let mut script = Vec::new();
+    completer.write_registration(
+        COMPLETE_VAR,
+        "animaforge",
+        "animaforge",
+        "animaforge",
+        &mut script,
+    )?;
+    print!("{}", String::from_utf8_lossy(&script));
+    Ok(())
+}
+
+/// User and project config files as written, without upgrading them or
+/// printing warnings in the middle of the command line
+fn config_tables() -> Vec<Table> {
+    let user = Config::config_path().ok();
+    let project = std::env::current_dir()
+        .ok()
+        .and_then(|dir| layers::find_project_file(&dir));
+
+    [user, project]
+        .into_iter()
+        .flatten()
+        .filter(|path| path.exists())
+        .filter_map(|path| layers::read_file(&path).ok())
+        .collect()
+}
+
+/// Names of the tables under `section`, e.g. the profiles
+fn table_names(section: &str) -> Vec<String> {
+    let mut names: Vec<String> = config_tables()
+        .iter()
+        .filter_map(|table| table.get(section).and_then(Value::as_table))
+        .flat_map(|tables| tables.keys().cloned())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+pub fn profiles() -> Vec<CompletionCandidate> {
+    let mut candidates: Vec<CompletionCandidate> = table_names("profiles")
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect();
+    candidates.push(CompletionCandidate::new(DEFAULT_PROFILE).help(Some("No profile".into())));
+    candidates
+}
+
+pub fn targets() -> Vec<CompletionCandidate> {
+    table_names("targets")
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+pub fn qualities() -> Vec<CompletionCandidate> {
+    ["low", "medium", "high"]
+        .into_iter()
+        .filter_map(|name| {
+            let settings = QualitySettings::from_name(name).ok()?;
+            Some(CompletionCandidate::new(name).help(Some(settings.resolution.into())))
+        })
+        .collect()
+}
+
+pub fn config_keys() -> Vec<CompletionCandidate> {
+    Config::known_keys()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// Models installed in Ollama; nothing when it doesn't answer quickly
+pub fn models() -> Vec<CompletionCandidate> {
+    let endpoint = ollama_endpoint();
+    let Ok(runtime) = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    else {
+        return Vec::new();
+    };
+
+    let models = runtime.block_on(async {
+        let client = OllamaClient::new(&endpoint);
+        tokio::time::timeout(OLLAMA_TIMEOUT, client.list_models()).await
+    });
+    match models {
+        Ok(Ok(models)) => models.into_iter().map(CompletionCandidate::new).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn ollama_endpoint() -> String {
+    if let Ok(endpoint) = std::env::var("ANIMAFORGE_OLLAMA_ENDPOINT") {
+        return endpoint;
+    }
+    // The project file comes last, so it wins
+    config_tables()
+        .iter()
+        .rev()
+        .find_map(|table| table.get("ollama_endpoint").and_then(Value::as_str))
+        .unwrap_or(DEFAULT_OLLAMA_ENDPOINT)
+        .to_string()
+}
+
+/// Scenes in the animation file named on the command line being completed
+pub fn scenes(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let Some(file) = animation_file(std::env::args_os().map(PathBuf::from)) else {
+        return Vec::new();
+    };
+
+    extract_scene_names(&file)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|scene| scene.starts_with(current.as_ref()))
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// The first existing Python file among `args`
+fn animation_file(args: impl Iterator<Item = PathBuf>) -> Option<PathBuf> {
+    args.filter(|arg| arg.extension().is_some_and(|ext| ext == "py"))
+        .find(|arg| arg.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_animation_file_in_args() {
+        let dir = std::env::temp_dir().join(format!("animaforge-complete-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("intro.py");
+        std::fs::write(&file, "class Intro(Scene):\n    pass\n").unwrap();
+
+        let args = ["animaforge", "--", "animaforge", "render", "missing.py"]
+            .into_iter()
+            .map(PathBuf::from)
+            .chain([file.clone(), PathBuf::from("--order")]);
+        assert_eq!(animation_file(args), Some(file));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
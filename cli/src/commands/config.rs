@@ -1,94 +1,110 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use serde_json::json;
+use std::fs;
+use std::io::IsTerminal;
+use std::process::Command;
+use toml::Value;
 
-use crate::cli::ConfigAction;
-use crate::config::Config;
+use crate::cli::{ConfigAction, ProfileAction};
+use crate::config::credentials;
+use crate::config::schema::{self, Kind};
+use crate::config::{Config, UnknownKey, DEFAULT_PROFILE};
+use crate::utils::output::{self, ErrorCode};
 
 pub fn execute(action: ConfigAction) -> Result<()> {
     match action {
-        ConfigAction::Set {
-            backend,
-            model,
-            api_key,
-            output_dir,
-        } => {
-            set_config(backend, model, api_key, output_dir)?;
+        ConfigAction::Set { key, value } => {
+            set_config(&key, &value)?;
+        }
+        ConfigAction::Unset { key } => {
+            unset_config(&key)?;
         }
         ConfigAction::Get { key } => {
             get_config(key)?;
         }
-        ConfigAction::List => {
-            list_config()?;
+        ConfigAction::List { show_origin } => {
+            if show_origin {
+                list_origins()?;
+            } else {
+                list_config()?;
+            }
+        }
+        ConfigAction::Edit => {
+            edit_config()?;
         }
+        ConfigAction::Encrypt => {
+            encrypt_credentials()?;
+        }
+        ConfigAction::Decrypt => {
+            decrypt_credentials()?;
+        }
+        ConfigAction::Profile { action } => match action {
+            ProfileAction::List => list_profiles()?,
+            ProfileAction::Create { name, from } => create_profile(&name, from.as_deref())?,
+            ProfileAction::Use { name } => use_profile(&name)?,
+            ProfileAction::Delete { name } => delete_profile(&name)?,
+        },
     }
 
     Ok(())
 }
 
-fn set_config(
-    backend: Option<String>,
-    model: Option<String>,
-    api_key: Option<String>,
-    output_dir: Option<String>,
-) -> Result<()> {
-    let mut config = Config::load().unwrap_or_default();
+fn set_config(key: &str, value: &str) -> Result<()> {
+    let mut config = Config::load()?;
+    config
+        .set(key, value)
+        .map_err(|e| output::with_code(ErrorCode::Config, e))?;
+    config.save()?;
 
-    let mut updated = false;
+    let value = config.get(key)?;
+    output::set_result(json!({
+        "key": key,
+        "value": value.as_ref().map(|v| json_value(key, v)),
+        "profile": profile_name(&config),
+    }));
 
-    if let Some(b) = backend {
-        // Validate backend
-        if !["ollama", "gemini", "claude"].contains(&b.as_str()) {
-            anyhow::bail!("Invalid backend. Valid options: ollama, gemini, claude");
-        }
-        println!(
-            "{} Setting backend to: {}",
-            "✓".green(),
-            b.bright_cyan()
-        );
-        config.backend = b;
-        updated = true;
-    }
+    let shown = value.map(|v| display(key, &v)).unwrap_or_default();
+    println!(
+        "{} Set {} to: {}{}",
+        "✓".green(),
+        key.bright_yellow(),
+        shown.bright_cyan(),
+        profile_note(&config)
+    );
 
-    if let Some(m) = model {
-        println!(
-            "{} Setting model to: {}",
-            "✓".green(),
-            m.bright_cyan()
-        );
-        config.model = m;
-        updated = true;
-    }
+    Ok(())
+}
 
-    if let Some(key) = api_key {
-        println!("{} API key updated", "✓".green());
-        config.api_key = Some(key);
-        updated = true;
+fn unset_config(key: &str) -> Result<()> {
+    let mut config = Config::load()?;
+    let changed = config.unset(key)?;
+    if changed {
+        config.save()?;
     }
 
-    if let Some(dir) = output_dir {
-        println!(
-            "{} Setting output directory to: {}",
-            "✓".green(),
-            dir.bright_cyan()
-        );
-        config.output_dir = Some(dir);
-        updated = true;
+    let value = config.get(key)?;
+    output::set_result(json!({
+        "key": key,
+        "changed": changed,
+        "value": value.as_ref().map(|v| json_value(key, v)),
+        "profile": profile_name(&config),
+    }));
+    if !changed {
+        println!("{} {} is not set", "→".bright_blue(), key);
+        return Ok(());
     }
 
-    if updated {
-        config.save()?;
-        println!(
-            "\n{} Configuration saved to: {}",
-            "✓".green().bold(),
-            Config::config_path()?.display().to_string().bright_cyan()
-        );
-    } else {
-        println!("{}", "No configuration changes specified".yellow());
-        println!("\nUsage examples:");
-        println!("  animaforge config set --backend ollama");
-        println!("  animaforge config set --model llama2");
-        println!("  animaforge config set --api-key YOUR_KEY");
-        println!("  animaforge config set --output-dir ./my_animations");
+    match value {
+        Some(default) => println!(
+            "{} Reset {} to: {}{}",
+            "✓".green(),
+            key.bright_yellow(),
+            display(key, &default).bright_cyan(),
+            profile_note(&config)
+        ),
+        None => println!("{} Unset {}", "✓".green(), key.bright_yellow()),
     }
 
     Ok(())
@@ -98,41 +114,253 @@ fn get_config(key: Option<String>) -> Result<()> {
     let config = Config::load()?;
 
     if let Some(k) = key {
-        match k.as_str() {
-            "backend" => println!("{}", config.backend),
-            "model" => println!("{}", config.model),
-            "api_key" => {
-                if let Some(key) = &config.api_key {
-                    println!("{}", mask_api_key(key));
-                } else {
-                    println!("(not set)");
-                }
+        let value = config.get(&k)?;
+        if value.is_some() || is_known(&config, &k) {
+            output::set_result(json!({
+                "key": k,
+                "value": value.as_ref().map(|v| json_value(&k, v)),
+            }));
+        }
+        match value {
+            Some(value) => println!("{}", display(&k, &value)),
+            None if is_known(&config, &k) => println!("(not set)"),
+            None => anyhow::bail!("Unknown config key: {}", k),
+        }
+    } else {
+        // Show all config
+        list_config()?;
+    }
+
+    Ok(())
+}
+
+/// " (profile gpu)" when changes go into a profile
+fn profile_note(config: &Config) -> String {
+    match &config.active_profile {
+        Some(active) => format!(" (profile {})", active.name).bright_black().to_string(),
+        None => String::new(),
+    }
+}
+
+/// Whether `key` names a field that is not set right now. Setting it on a
+/// copy tells, since only unknown keys fail with `UnknownKey`.
+fn is_known(config: &Config, key: &str) -> bool {
+    let mut probe = config.clone();
+    !matches!(probe.set(key, "0"), Err(e) if e.is::<UnknownKey>())
+}
+
+fn profile_name(config: &Config) -> Option<&str> {
+    config.active_profile.as_ref().map(|active| active.name.as_str())
+}
+
+/// Every set value with where it came from, for `--json`
+fn record_values(config: &Config) -> Result<()> {
+    let mut values = Vec::new();
+    for (key, origin) in config.origins() {
+        if key.starts_with("profiles.") {
+            continue;
+        }
+        if let Some(value) = config.get(&key)? {
+            values.push(json!({
+                "key": key,
+                "value": json_value(&key, &value),
+                "origin": origin.to_string(),
+            }));
+        }
+    }
+    output::set_result(json!({
+        "profile": profile_name(config),
+        "values": values,
+    }));
+    Ok(())
+}
+
+/// A value for `--json`, with secrets masked
+fn json_value(key: &str, value: &Value) -> serde_json::Value {
+    match (schema::kind(key), value) {
+        (Kind::Secret, Value::String(secret)) => json!(mask_api_key(secret)),
+        _ => serde_json::to_value(value).unwrap_or_default(),
+    }
+}
+
+/// A value as the user would type it, with secrets masked
+fn display(key: &str, value: &Value) -> String {
+    match (schema::kind(key), value) {
+        (Kind::Secret, Value::String(secret)) => mask_api_key(secret),
+        (_, Value::String(text)) => text.clone(),
+        (_, value) => value.to_string(),
+    }
+}
+
+/// Open the config in $VISUAL or $EDITOR and only save it once it is valid
+fn edit_config() -> Result<()> {
+    let path = Config::config_path()?;
+    let original = if path.exists() {
+        fs::read_to_string(&path).context("Failed to read config file")?
+    } else {
+        toml::to_string_pretty(&Config::default()).context("Failed to serialize config")?
+    };
+
+    fs::create_dir_all(Config::config_dir()?).context("Failed to create config directory")?;
+    let draft = Config::config_dir()?.join("config.edit.toml");
+    fs::write(&draft, &original).context("Failed to write config draft")?;
+
+    loop {
+        open_editor(&draft)?;
+        let content = fs::read_to_string(&draft).context("Failed to read config draft")?;
+
+        if content == original {
+            fs::remove_file(&draft).ok();
+            output::set_result(json!({ "config": path.display().to_string(), "changed": false }));
+            println!("{}", "No changes".yellow());
+            return Ok(());
+        }
+
+        match Config::parse(&content) {
+            Ok(_) => {
+                fs::write(&path, &content).context("Failed to write config file")?;
+                fs::remove_file(&draft).ok();
+                output::set_result(json!({ "config": path.display().to_string(), "changed": true }));
+                println!(
+                    "{} Configuration saved to: {}",
+                    "✓".green().bold(),
+                    path.display().to_string().bright_cyan()
+                );
+                return Ok(());
             }
-            "output_dir" => {
-                if let Some(dir) = &config.output_dir {
-                    println!("{}", dir);
-                } else {
-                    println!("./animations (default)");
+            Err(e) => {
+                eprintln!("{} {:#}", "✗".red(), e);
+                let again = std::io::stdin().is_terminal()
+                    && Confirm::with_theme(&ColorfulTheme::default())
+                        .with_prompt("Edit again?")
+                        .default(true)
+                        .interact()?;
+                if !again {
+                    anyhow::bail!(
+                        "Configuration not saved. Your edits are in {}",
+                        draft.display()
+                    );
                 }
             }
-            _ => {
-                anyhow::bail!("Unknown config key: {}", k);
-            }
         }
+    }
+}
+
+/// Encrypt the plaintext secrets in the credentials file with a passphrase
+fn encrypt_credentials() -> Result<()> {
+    // Loading moves any secrets still in config.toml to the credentials file
+    Config::load_base()?;
+    let path = Config::credentials_path()?;
+    let mut secrets = if path.exists() {
+        credentials::read(&path)?
     } else {
-        // Show all config
-        list_config()?;
+        toml::Table::new()
+    };
+    if schema::leaves(&secrets, "").is_empty() {
+        anyhow::bail!("No secrets to encrypt. Set one with `animaforge config set api_key <KEY>`");
+    }
+
+    // Every secret uses the same passphrase
+    let existing = encrypted_value(&secrets);
+    let passphrase = credentials::passphrase(existing.is_none())?;
+    if let Some(existing) = existing {
+        credentials::decrypt(&existing, &passphrase)?;
+    }
+
+    let count = credentials::encrypt_all(&mut secrets, &passphrase)?;
+    credentials::write(&path, &secrets)?;
+
+    output::set_result(json!({ "credentials": path.display().to_string(), "encrypted": count }));
+    println!(
+        "{} Encrypted {} secret(s) in {}",
+        "✓".green(),
+        count,
+        path.display().to_string().bright_cyan()
+    );
+    println!(
+        "{} Set {} to use them without a prompt, e.g. in CI",
+        "Tip:".bright_blue(),
+        credentials::PASSPHRASE_VAR.bright_cyan()
+    );
+    Ok(())
+}
+
+fn decrypt_credentials() -> Result<()> {
+    let path = Config::credentials_path()?;
+    let mut secrets = if path.exists() {
+        credentials::read(&path)?
+    } else {
+        toml::Table::new()
+    };
+    if encrypted_value(&secrets).is_none() {
+        output::set_result(json!({ "credentials": path.display().to_string(), "decrypted": 0 }));
+        println!("{} No encrypted secrets", "✓".green());
+        return Ok(());
     }
 
+    let passphrase = credentials::passphrase(false)?;
+    let count = credentials::decrypt_all(&mut secrets, &passphrase)?;
+    credentials::write(&path, &secrets)?;
+
+    output::set_result(json!({ "credentials": path.display().to_string(), "decrypted": count }));
+    println!(
+        "{} Decrypted {} secret(s) in {}",
+        "✓".green(),
+        count,
+        path.display().to_string().bright_cyan()
+    );
+    Ok(())
+}
+
+fn encrypted_value(secrets: &toml::Table) -> Option<String> {
+    schema::leaves(secrets, "")
+        .into_iter()
+        .filter_map(|(_, value)| value.as_str())
+        .find(|value| credentials::is_encrypted(value))
+        .map(str::to_string)
+}
+
+fn open_editor(file: &std::path::Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+
+    // Allow editors with arguments, e.g. "code --wait"
+    let mut words = editor.split_whitespace();
+    let program = words.next().context("$EDITOR is empty")?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(file)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {}", editor, status);
+    }
     Ok(())
 }
 
 fn list_config() -> Result<()> {
     let config = Config::load()?;
+    record_values(&config)?;
 
     println!("{}", "Current Configuration:".bright_green().bold());
     println!("────────────────────────────────────");
 
+    match &config.active_profile {
+        Some(active) => println!(
+            "{:15} {} {}",
+            "Profile:".bright_yellow(),
+            active.name.bright_cyan(),
+            format!("(from {})", active.source).bright_black()
+        ),
+        None => println!(
+            "{:15} {}",
+            "Profile:".bright_yellow(),
+            "(none)".bright_black()
+        ),
+    }
+
     println!(
         "{:15} {}",
         "Backend:".bright_yellow(),
@@ -173,6 +401,12 @@ fn list_config() -> Result<()> {
         );
     }
 
+    println!(
+        "{:15} {}",
+        "Quality:".bright_yellow(),
+        config.quality().bright_cyan()
+    );
+
     if let Some(endpoint) = &config.ollama_endpoint {
         println!(
             "{:15} {}",
@@ -181,17 +415,232 @@ fn list_config() -> Result<()> {
         );
     }
 
+    println!(
+        "{:15} {}",
+        "Marketplace:".bright_yellow(),
+        config.marketplace_url().bright_cyan()
+    );
+
+    println!(
+        "{:15} {}",
+        "Market Token:".bright_yellow(),
+        match &config.marketplace_token {
+            Some(token) => mask_api_key(token).bright_cyan(),
+            None => "(not set)".bright_black(),
+        }
+    );
+
+    println!(
+        "{:15} {}",
+        "Sandbox:".bright_yellow(),
+        config.sandbox.as_deref().unwrap_or("never").bright_cyan()
+    );
+
+    let thumbnails = &config.thumbnails;
+    let thumbnail_summary = if thumbnails.enabled {
+        format!(
+            "{} {}px at {}s{}",
+            thumbnails.format,
+            thumbnails.width,
+            thumbnails.at,
+            if thumbnails.gif { " + gif" } else { "" }
+        )
+    } else {
+        "off".to_string()
+    };
+    println!(
+        "{:15} {}",
+        "Thumbnails:".bright_yellow(),
+        thumbnail_summary.bright_cyan()
+    );
+
     println!("────────────────────────────────────");
     println!(
         "\n{} {}",
         "Config file:".bright_black(),
         Config::config_path()?.display().to_string().bright_black()
     );
+    if let Some(project) = config.layers.as_ref().and_then(|l| l.project.as_ref()) {
+        println!(
+            "{} {}",
+            "Project file:".bright_black(),
+            project.display().to_string().bright_black()
+        );
+    }
+
+    Ok(())
+}
+
+/// Every value with the file, variable or flag it came from
+fn list_origins() -> Result<()> {
+    let config = Config::load()?;
+    record_values(&config)?;
+
+    for (key, origin) in config.origins() {
+        if key.starts_with("profiles.") {
+            continue;
+        }
+        let Some(value) = config.get(&key)? else {
+            continue;
+        };
+        println!(
+            "{}  {} = {}",
+            origin.to_string().bright_black(),
+            key.bright_yellow(),
+            display(&key, &value).bright_cyan()
+        );
+    }
+
+    Ok(())
+}
+
+fn list_profiles() -> Result<()> {
+    let base = Config::load_base()?;
+    let active = Config::load().ok().and_then(|config| config.active_profile);
+
+    output::set_result(json!({
+        "active": active.as_ref().map(|a| &a.name),
+        "profiles": base.profiles.keys().collect::<Vec<_>>(),
+    }));
+    if base.profiles.is_empty() {
+        println!("{}", "No profiles yet.".yellow());
+        println!(
+            "\n{} Create one with: {}",
+            "Tip:".bright_blue(),
+            "animaforge config profile create gpu".bright_cyan()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "Profiles:".bright_green().bold());
+    for (name, overrides) in &base.profiles {
+        let is_active = active.as_ref().is_some_and(|a| a.name == *name);
+        let keys: Vec<String> = schema::leaves(overrides, "")
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        let keys = if keys.is_empty() {
+            "(no overrides)".to_string()
+        } else {
+            keys.join(", ")
+        };
+        println!(
+            "{} {:<16} {}",
+            if is_active { "*".green() } else { " ".normal() },
+            if is_active {
+                name.bright_green()
+            } else {
+                name.bright_cyan()
+            },
+            keys.bright_black()
+        );
+    }
+
+    match active {
+        Some(active) => println!(
+            "\n{} {} (from {})",
+            "Active:".bright_yellow(),
+            active.name.bright_cyan(),
+            active.source
+        ),
+        None => println!("\n{} none", "Active:".bright_yellow()),
+    }
+
+    Ok(())
+}
+
+fn create_profile(name: &str, from: Option<&str>) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        anyhow::bail!("Profile names may only contain letters, digits, '-' and '_'");
+    }
+    if name == DEFAULT_PROFILE {
+        anyhow::bail!("'{}' means no profile and can't be created", DEFAULT_PROFILE);
+    }
+
+    let mut config = Config::load_base()?;
+    if config.profiles.contains_key(name) {
+        anyhow::bail!("Profile '{}' already exists", name);
+    }
+
+    let overrides = match from {
+        Some(from) => config
+            .profiles
+            .get(from)
+            .cloned()
+            .with_context(|| format!("Unknown profile '{}'", from))?,
+        None => Default::default(),
+    };
+    config.profiles.insert(name.to_string(), overrides);
+    config.save()?;
+
+    output::set_result(json!({ "profile": name }));
+    println!("{} Created profile {}", "✓".green(), name.bright_cyan());
+    println!(
+        "\n{} Set its values with: {}",
+        "Tip:".bright_blue(),
+        format!("animaforge --profile {} config set <KEY> <VALUE>", name).bright_cyan()
+    );
+
+    Ok(())
+}
+
+fn use_profile(name: &str) -> Result<()> {
+    let mut config = Config::load_base()?;
+
+    if name == DEFAULT_PROFILE {
+        config.profile = None;
+    } else if config.profiles.contains_key(name) {
+        config.profile = Some(name.to_string());
+    } else {
+        anyhow::bail!("Unknown profile '{}'", name);
+    }
+    config.save()?;
+
+    output::set_result(json!({ "profile": config.profile }));
+    println!(
+        "{} Using {} by default",
+        "✓".green(),
+        if name == DEFAULT_PROFILE {
+            "no profile".to_string()
+        } else {
+            format!("profile {}", name.bright_cyan())
+        }
+    );
+    if let Ok(env) = std::env::var("ANIMAFORGE_PROFILE") {
+        println!(
+            "{} ANIMAFORGE_PROFILE={} takes precedence in this shell",
+            "⚠".yellow(),
+            env
+        );
+    }
+
+    Ok(())
+}
+
+fn delete_profile(name: &str) -> Result<()> {
+    let mut config = Config::load_base()?;
+    if config.profiles.remove(name).is_none() {
+        anyhow::bail!("Unknown profile '{}'", name);
+    }
+    if config.profile.as_deref() == Some(name) {
+        config.profile = None;
+    }
+    config.save()?;
+
+    output::set_result(json!({ "profile": name }));
+    println!("{} Deleted profile {}", "✓".green(), name.bright_cyan());
 
     Ok(())
 }
 
 fn mask_api_key(key: &str) -> String {
+    if let Some(shown) = credentials::describe(key) {
+        return shown.to_string();
+    }
     if key.len() <= 8 {
         return "********".to_string();
     }
@@ -1,11 +1,14 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
+use serde_json::json;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::commands::render::RenderOptions;
 use crate::config::Config;
 use crate::llm::ollama::OllamaClient;
-use crate::utils::progress;
+use crate::sidecar::{self, Sidecar};
+use crate::utils::{output, progress};
 
 pub async fn execute(prompt: String, auto_render: bool, output: Option<String>) -> Result<()> {
     println!("{}", "Creating animation from prompt...".bright_green().bold());
@@ -20,9 +23,7 @@ pub async fn execute(prompt: String, auto_render: bool, output: Option<String>)
     // Generate code using LLM
     let animation_code = match config.backend.as_str() {
         "ollama" => {
-            let client = OllamaClient::new(
-                config.ollama_endpoint.as_deref().unwrap_or("http://localhost:11434")
-            );
+            let client = OllamaClient::new(config.ollama_endpoint());
             client.generate_animation_code(&prompt, &config.model).await?
         }
         "gemini" | "claude" => {
@@ -54,21 +55,35 @@ pub async fn execute(prompt: String, auto_render: bool, output: Option<String>)
     fs::write(&output_path, &animation_code)
         .context("Failed to save animation code")?;
 
+    // Record where the code came from; renders treat it as trusted while unedited
+    Sidecar::generated(&prompt, &config.backend, &config.model, &animation_code)
+        .save(&output_path)?;
+    sidecar::trust(&output_path)?;
+
     println!(
         "\n{} Animation code saved to: {}",
         "✓".green(),
         output_path.display().to_string().bright_cyan()
     );
 
+    let mut result = json!({
+        "file": output_path.display().to_string(),
+        "backend": config.backend,
+        "model": config.model,
+    });
+    output::set_result(&result);
+
     // Auto-render if requested
     if auto_render {
         println!("\n{}", "Auto-rendering enabled...".bright_yellow());
-        crate::commands::render::execute(
+        let rendered = crate::commands::render::execute(
             output_path.to_string_lossy().to_string(),
-            "medium".to_string(),
-            None,
+            RenderOptions::from_config(&config, config.quality()),
         )
-        .await?;
+        .await;
+        result["render"] = output::take_result().unwrap_or_default();
+        output::set_result(&result);
+        rendered?;
     }
 
     println!("\n{}", "✨ Animation creation complete!".bright_green().bold());
@@ -119,7 +134,7 @@ class GeneratedAnimation(Scene):
     )
 }
 
-fn validate_code(code: &str) -> Result<bool> {
+pub fn validate_code(code: &str) -> Result<bool> {
     // Basic validation: check if it's valid Python and contains Manim imports
     let has_manim_import = code.contains("from manim import") || code.contains("import manim");
     let has_scene_class = code.contains("class") && code.contains("Scene");
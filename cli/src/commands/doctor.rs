@@ -0,0 +1,419 @@
+use anyhow::Result;
+use colored::Colorize;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::commands::models::is_same_model;
+use crate::config::Config;
+use crate::llm::ollama::OllamaClient;
+use crate::utils::output::{self, ErrorCode};
+use crate::utils::{progress, system};
+
+
+/// How long to wait for the marketplace before calling it unreachable
+const MARKETPLACE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// Outcome of one diagnostic
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    status: Status,
+    checks: &'a [Check],
+}
+
+pub async fn execute() -> Result<()> {
+    let spinner = progress::create_spinner("Checking your setup...");
+
+    let (config, config_check) = check_config();
+    let mut checks = vec![config_check];
+    checks.extend(check_backend(&config).await);
+    for tool in [system::PYTHON, system::MANIM, system::FFMPEG, system::LATEX] {
+        checks.push(check_tool(&tool));
+    }
+    checks.push(check_output_dir(&config));
+    checks.extend(check_marketplace(&config).await);
+
+    spinner.finish_and_clear();
+
+    let status = checks
+        .iter()
+        .map(|check| check.status)
+        .max()
+        .unwrap_or(Status::Pass);
+
+    output::set_result(Report {
+        status,
+        checks: &checks,
+    });
+    if !output::json() {
+        print_report(&checks);
+    }
+
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        return Err(output::failure(
+            ErrorCode::CheckFailed,
+            format!("{} check{} failed", failed, if failed == 1 { "" } else { "s" }),
+        ));
+    }
+
+    Ok(())
+}
+
+fn print_report(checks: &[Check]) {
+    println!("{}\n", "AnimaForge doctor".bright_green().bold());
+
+    for check in checks {
+        let icon = match check.status {
+            Status::Pass => "✓".green(),
+            Status::Warn => "⚠".yellow(),
+            Status::Fail => "✗".red(),
+        };
+        println!("  {} {:<12} {}", icon, check.name, check.detail);
+        if let Some(fix) = &check.fix {
+            println!(
+                "    {:<12} {} {}",
+                "",
+                "→".bright_blue(),
+                fix.bright_black()
+            );
+        }
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let (warnings, failures) = (count(Status::Warn), count(Status::Fail));
+    println!();
+    if warnings == 0 && failures == 0 {
+        println!("{} Everything looks good", "✨".bright_green());
+    } else {
+        println!(
+            "{} passed, {} warning{}, {} failed",
+            count(Status::Pass).to_string().green(),
+            warnings.to_string().yellow(),
+            if warnings == 1 { "" } else { "s" },
+            failures.to_string().red()
+        );
+    }
+}
+
+/// Load the config, falling back to the defaults so the other checks still run
+fn check_config() -> (Config, Check) {
+    let path = match Config::config_path() {
+        Ok(path) => path,
+        Err(e) => {
+            return (
+                Config::default(),
+                Check::fail(
+                    "config",
+                    format!("{:#}", e),
+                    "Set the HOME environment variable",
+                ),
+            )
+        }
+    };
+
+    if !path.exists() {
+        return (
+            Config::default(),
+            Check::warn(
+                "config",
+                format!("{} not found, using defaults", path.display()),
+                "Run `animaforge init`",
+            ),
+        );
+    }
+
+    match Config::load() {
+        Ok(config) => (config, Check::pass("config", path.display().to_string())),
+        Err(e) => (
+            Config::default(),
+            Check::fail(
+                "config",
+                // TOML errors continue with a multi-line excerpt of the file
+                format!("{:#}", e).lines().next().unwrap_or_default(),
+                format!("Fix {} or rerun `animaforge init`", path.display()),
+            ),
+        ),
+    }
+}
+
+/// The backend is reachable and has the configured model
+async fn check_backend(config: &Config) -> Vec<Check> {
+    match config.backend.as_str() {
+        "ollama" => {
+            let endpoint = config.ollama_endpoint();
+            let client = OllamaClient::new(endpoint);
+
+            if !client.check_connection().await.unwrap_or(false) {
+                return vec![
+                    Check::fail(
+                        "backend",
+                        format!("Ollama is not reachable at {}", endpoint),
+                        "Start it with `ollama serve` or run `animaforge config set ollama_endpoint <URL>`",
+                    ),
+                    Check::warn(
+                        "model",
+                        format!("Could not check {}", config.model),
+                        "Fix the backend first",
+                    ),
+                ];
+            }
+
+            let backend = Check::pass("backend", format!("Ollama at {}", endpoint));
+            let model = match client.list_models().await {
+                Ok(models) if models.iter().any(|m| is_same_model(m, &config.model)) => {
+                    Check::pass("model", config.model.clone())
+                }
+                Ok(models) if models.is_empty() => Check::fail(
+                    "model",
+                    format!("{} is not installed and Ollama has no models", config.model),
+                    format!("Run `ollama pull {}`", config.model),
+                ),
+                Ok(models) => Check::fail(
+                    "model",
+                    format!(
+                        "{} is not installed (available: {})",
+                        config.model,
+                        models.join(", ")
+                    ),
+                    format!(
+                        "Run `ollama pull {}` or `animaforge models use <name>`",
+                        config.model
+                    ),
+                ),
+                Err(e) => Check::warn(
+                    "model",
+                    format!("Could not list Ollama models: {:#}", e),
+                    "Check that the endpoint is an Ollama server",
+                ),
+            };
+            vec![backend, model]
+        }
+        "gemini" | "claude" => {
+            let backend = match config.api_key() {
+                Ok(Some(key)) if !key.is_empty() => Check::warn(
+                    "backend",
+                    format!(
+                        "{} is not implemented yet; create uses mock code",
+                        config.backend
+                    ),
+                    "Use the ollama backend for real generations",
+                ),
+                Ok(_) => Check::fail(
+                    "backend",
+                    format!("{} needs an API key", config.backend),
+                    "Run `animaforge config set api_key <KEY>`",
+                ),
+                Err(e) => Check::fail(
+                    "backend",
+                    format!("{:#}", e),
+                    "Set the variable or run `animaforge config set api_key <KEY>`",
+                ),
+            };
+            vec![backend, Check::pass("model", config.model.clone())]
+        }
+        other => vec![Check::fail(
+            "backend",
+            format!("Unknown backend '{}'", other),
+            "Run `animaforge config set backend ollama`",
+        )],
+    }
+}
+
+fn check_tool(tool: &system::Tool) -> Check {
+    match tool.version() {
+        Some(version) => Check::pass(tool.name, version),
+        None if tool.required => Check::fail(tool.name, "not found", install_hint(tool)),
+        None => Check::warn(tool.name, "not found", install_hint(tool)),
+    }
+}
+
+fn install_hint(tool: &system::Tool) -> String {
+    let mut hint = tool.install.to_string();
+    if let Some(first) = hint.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    hint
+}
+
+fn check_output_dir(config: &Config) -> Check {
+    let dir = PathBuf::from(config.output_dir.as_deref().unwrap_or("./animations"));
+
+    match writable(&dir) {
+        Ok(()) if dir.exists() => Check::pass("output dir", dir.display().to_string()),
+        Ok(()) => Check::pass(
+            "output dir",
+            format!("{} (created on the first render)", dir.display()),
+        ),
+        Err(e) => Check::fail(
+            "output dir",
+            format!("{} is not writable: {}", dir.display(), e),
+            "Fix its permissions or run `animaforge config set output_dir <DIR>`",
+        ),
+    }
+}
+
+/// Write a probe file into `dir`, or into its closest existing parent when
+/// `dir` does not exist yet
+fn writable(dir: &Path) -> std::io::Result<()> {
+    let existing = dir
+        .ancestors()
+        .find(|path| path.as_os_str().is_empty() || path.exists())
+        .filter(|path| !path.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    let probe = existing.join(format!(".animaforge-doctor-{}", std::process::id()));
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)
+}
+
+/// The marketplace answers and accepts the token. Both are optional, so
+/// problems are warnings unless the token is rejected.
+async fn check_marketplace(config: &Config) -> Vec<Check> {
+    let url = config.marketplace_url().to_string();
+    let client = match reqwest::Client::builder()
+        .timeout(MARKETPLACE_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            return vec![Check::warn(
+                "marketplace",
+                format!("{:#}", e),
+                "Check your TLS setup",
+            )]
+        }
+    };
+
+    let health = client.get(format!("{}/api/v1/health", url)).send().await;
+    match health {
+        Ok(response) if response.status().is_success() => {}
+        Ok(response) => {
+            return vec![Check::warn(
+                "marketplace",
+                format!("{} answered {}", url, response.status()),
+                "Check marketplace_url points at the marketplace API",
+            )]
+        }
+        Err(_) => {
+            return vec![Check::warn(
+                "marketplace",
+                format!("{} is not reachable", url),
+                "Start the marketplace API or run: animaforge config set marketplace_url <URL>",
+            )]
+        }
+    }
+
+    let marketplace = Check::pass("marketplace", url.clone());
+    let token = match config.marketplace_token() {
+        Err(e) => Check::fail(
+            "token",
+            format!("{:#}", e),
+            "Set the variable or run `animaforge config set marketplace_token <TOKEN>`",
+        ),
+        Ok(None) => Check::warn(
+            "token",
+            "No marketplace token; publishing needs one",
+            "Log in to the marketplace and run `animaforge config set marketplace_token <TOKEN>`",
+        ),
+        Ok(Some(token)) => {
+            let response = client
+                .get(format!("{}/api/v1/auth/me", url))
+                .bearer_auth(token)
+                .send()
+                .await;
+            match response {
+                Ok(response) if response.status().is_success() => {
+                    Check::pass("token", "accepted by the marketplace")
+                }
+                Ok(response)
+                    if response.status() == reqwest::StatusCode::UNAUTHORIZED
+                        || response.status() == reqwest::StatusCode::FORBIDDEN =>
+                {
+                    Check::fail(
+                        "token",
+                        "rejected by the marketplace (expired or invalid)",
+                        "Log in again and run `animaforge config set marketplace_token <TOKEN>`",
+                    )
+                }
+                Ok(response) => Check::warn(
+                    "token",
+                    format!("could not be verified ({})", response.status()),
+                    "Try again later",
+                ),
+                Err(e) => Check::warn(
+                    "token",
+                    format!("could not be verified: {}", e),
+                    "Try again later",
+                ),
+            }
+        }
+    };
+
+    vec![marketplace, token]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn probes_closest_existing_directory() {
+        let dir =
+            std::env::temp_dir().join(format!("animaforge-doctor-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        assert!(writable(&dir).is_ok());
+        assert!(writable(&dir.join("not/created/yet")).is_ok());
+        assert!(!dir.join("not").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
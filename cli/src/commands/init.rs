@@ -0,0 +1,334 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, Password, Select};
+use serde_json::json;
+use std::io::IsTerminal;
+
+use crate::commands::create;
+use crate::commands::render::QualitySettings;
+use crate::config::Config;
+use crate::llm::ollama::OllamaClient;
+use crate::utils::{output, progress, system};
+
+const BACKENDS: [&str; 3] = ["ollama", "gemini", "claude"];
+const QUALITIES: [&str; 3] = ["low", "medium", "high"];
+
+/// Prompt for the test generation at the end of the wizard
+const TEST_PROMPT: &str = "A blue circle that transforms into a red square";
+
+/// Answers given as flags; anything left out is asked for, or defaulted with `--yes`
+#[derive(Debug, Clone, Default)]
+pub struct InitOptions {
+    pub backend: Option<String>,
+    pub model: Option<String>,
+    pub api_key: Option<String>,
+    pub ollama_endpoint: Option<String>,
+    pub output_dir: Option<String>,
+    pub quality: Option<String>,
+    /// Don't ask anything
+    pub yes: bool,
+    pub skip_test: bool,
+}
+
+pub async fn execute(options: InitOptions) -> Result<()> {
+    let interactive = !options.yes && !output::is_scripted() && std::io::stdin().is_terminal();
+    let theme = ColorfulTheme::default();
+    // Saving over a config that failed to load would throw away its settings
+    let mut config = Config::load()?;
+
+    println!("{}", "AnimaForge setup".bright_green().bold());
+    if Config::exists() {
+        println!(
+            "{} Updating {}",
+            "→".bright_blue(),
+            Config::config_path()?.display().to_string().bright_cyan()
+        );
+    }
+    println!();
+
+    if let Some(backend) = &options.backend {
+        if !BACKENDS.contains(&backend.as_str()) {
+            anyhow::bail!("Invalid backend. Valid options: {}", BACKENDS.join(", "));
+        }
+    }
+    if let Some(quality) = &options.quality {
+        QualitySettings::from_name(quality)?;
+    }
+
+    // 1. Ollama
+    let endpoint = options
+        .ollama_endpoint
+        .clone()
+        .unwrap_or_else(|| config.ollama_endpoint().to_string());
+    let spinner = progress::create_spinner("Looking for Ollama...");
+    let models = detect_ollama(&endpoint).await;
+    spinner.finish_and_clear();
+    match &models {
+        Some(models) => println!(
+            "{} Ollama is running at {} with {} model{}",
+            "✓".green(),
+            endpoint.bright_cyan(),
+            models.len(),
+            if models.len() == 1 { "" } else { "s" }
+        ),
+        None => println!(
+            "{} No Ollama found at {}",
+            "⚠".yellow(),
+            endpoint.bright_cyan()
+        ),
+    }
+
+    // 2. Backend, model and key
+    let backend = match options.backend.clone() {
+        Some(backend) => backend,
+        None if interactive => {
+            let default = if models.is_some() {
+                0
+            } else {
+                BACKENDS
+                    .iter()
+                    .position(|b| *b == config.backend)
+                    .unwrap_or(0)
+            };
+            let index = Select::with_theme(&theme)
+                .with_prompt("AI backend")
+                .items(&BACKENDS)
+                .default(default)
+                .interact()?;
+            BACKENDS[index].to_string()
+        }
+        None if models.is_some() => "ollama".to_string(),
+        None => config.backend.clone(),
+    };
+
+    let model = match options.model.clone() {
+        Some(model) => model,
+        None => choose_model(&backend, models.as_deref(), &config, interactive, &theme)?,
+    };
+
+    let mut api_key = options.api_key.clone();
+    if backend != "ollama" && api_key.is_none() && interactive {
+        let key = Password::with_theme(&theme)
+            .with_prompt(format!(
+                "{} API key (leave empty to keep the current one)",
+                backend
+            ))
+            .allow_empty_password(true)
+            .interact()?;
+        api_key = Some(key).filter(|k| !k.is_empty());
+    }
+
+    // 3. Tools
+    println!("\n{}", "Checking tools:".bright_yellow());
+    for tool in system::RENDER_TOOLS {
+        match tool.version() {
+            Some(version) => println!(
+                "  {} {:<8} {}",
+                "✓".green(),
+                tool.name,
+                version.bright_black()
+            ),
+            None if tool.required => {
+                println!(
+                    "  {} {:<8} not found: {}",
+                    "✗".red(),
+                    tool.name,
+                    tool.install
+                )
+            }
+            None => println!(
+                "  {} {:<8} not found: {}",
+                "⚠".yellow(),
+                tool.name,
+                tool.install
+            ),
+        }
+    }
+    println!();
+
+    // 4. Output
+    let output_dir = match options.output_dir.clone() {
+        Some(dir) => dir,
+        None if interactive => Input::with_theme(&theme)
+            .with_prompt("Output directory")
+            .default(
+                config
+                    .output_dir
+                    .clone()
+                    .unwrap_or_else(|| "./animations".to_string()),
+            )
+            .interact_text()?,
+        None => config
+            .output_dir
+            .clone()
+            .unwrap_or_else(|| "./animations".to_string()),
+    };
+
+    let quality = match options.quality.clone() {
+        Some(quality) => quality,
+        None if interactive => {
+            let current = QUALITIES
+                .iter()
+                .position(|q| *q == config.quality())
+                .unwrap_or(1);
+            let index = Select::with_theme(&theme)
+                .with_prompt("Default render quality")
+                .items(&QUALITIES)
+                .default(current)
+                .interact()?;
+            QUALITIES[index].to_string()
+        }
+        None => config.quality().to_string(),
+    };
+
+    // 5. Save
+    config.backend = backend;
+    config.model = model;
+    if api_key.is_some() {
+        config.api_key = api_key;
+    }
+    if config.backend == "ollama" {
+        config.ollama_endpoint = Some(endpoint);
+    }
+    config.output_dir = Some(output_dir);
+    config.quality = Some(quality);
+    config.save()?;
+
+    let path = Config::config_path()?;
+    output::set_result(json!({
+        "config": path.display().to_string(),
+        "backend": config.backend,
+        "model": config.model,
+    }));
+    println!(
+        "{} Configuration saved to: {}",
+        "✓".green().bold(),
+        path.display().to_string().bright_cyan()
+    );
+
+    // 6. Try it
+    let run_test = !options.skip_test
+        && (!interactive
+            || Confirm::with_theme(&theme)
+                .with_prompt("Run a test generation?")
+                .default(true)
+                .interact()?);
+    if run_test {
+        test_generation(&config).await?;
+    }
+
+    println!(
+        "\n{} You're all set. Try: {}",
+        "✨".bright_green(),
+        "animaforge create \"a sine wave being drawn\" --render".bright_cyan()
+    );
+
+    Ok(())
+}
+
+/// Models of a running Ollama, or `None` if it is not reachable
+async fn detect_ollama(endpoint: &str) -> Option<Vec<String>> {
+    let client = OllamaClient::new(endpoint);
+    client.list_models().await.ok()
+}
+
+fn choose_model(
+    backend: &str,
+    models: Option<&[String]>,
+    config: &Config,
+    interactive: bool,
+    theme: &ColorfulTheme,
+) -> Result<String> {
+    // Keep the current model when the backend does not change
+    let current = (Config::exists() && config.backend == backend).then(|| config.model.clone());
+
+    match (backend, models) {
+        ("ollama", Some(models)) if !models.is_empty() => {
+            if !interactive {
+                return Ok(current
+                    .filter(|m| models.contains(m))
+                    .unwrap_or_else(|| models[0].clone()));
+            }
+            let default = current
+                .and_then(|m| models.iter().position(|name| *name == m))
+                .unwrap_or(0);
+            let index = Select::with_theme(theme)
+                .with_prompt("Model")
+                .items(models)
+                .default(default)
+                .interact()?;
+            Ok(models[index].clone())
+        }
+        ("ollama", _) if !interactive => current
+            .context("No Ollama models found. Pull one with `ollama pull <model>` or pass --model"),
+        _ => {
+            let default = current.unwrap_or_else(|| default_model(backend).to_string());
+            if !interactive {
+                return Ok(default);
+            }
+            if backend == "ollama" {
+                println!(
+                    "{} Pull the model before generating: {}",
+                    "→".bright_blue(),
+                    "ollama pull <model>".bright_cyan()
+                );
+            }
+            Ok(Input::with_theme(theme)
+                .with_prompt("Model")
+                .default(default)
+                .interact_text()?)
+        }
+    }
+}
+
+fn default_model(backend: &str) -> &'static str {
+    match backend {
+        "gemini" => "gemini-pro",
+        "claude" => "claude-3-5-sonnet-latest",
+        _ => "llama3",
+    }
+}
+
+/// Generate a small animation with the new settings and show its start
+async fn test_generation(config: &Config) -> Result<()> {
+    if config.backend != "ollama" {
+        println!(
+            "{} Test generation skipped: the {} backend is not implemented yet",
+            "⚠".yellow(),
+            config.backend
+        );
+        return Ok(());
+    }
+
+    let spinner = progress::create_spinner("Generating a test animation...");
+    let client = OllamaClient::new(config.ollama_endpoint());
+    let code = client
+        .generate_animation_code(TEST_PROMPT, &config.model)
+        .await;
+    spinner.finish_and_clear();
+
+    let code = match code {
+        Ok(code) => code,
+        Err(e) => {
+            println!("{} Test generation failed: {:#}", "✗".red(), e);
+            return Ok(());
+        }
+    };
+
+    if create::validate_code(&code)? {
+        println!("{} Test generation works:", "✓".green());
+    } else {
+        println!(
+            "{} The model answered, but not with valid Manim code:",
+            "⚠".yellow()
+        );
+    }
+    for line in code.lines().take(12) {
+        println!("    {}", line.bright_black());
+    }
+    if code.lines().count() > 12 {
+        println!("    {}", "...".bright_black());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use colored::Colorize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cli::Cli;
+use crate::utils::output;
+
+/// Print the man page, or write one page per command into `out_dir`
+pub fn execute(out_dir: Option<String>) -> Result<()> {
+    let command = Cli::command();
+
+    let Some(out_dir) = out_dir else {
+        let mut page = Vec::new();
+        clap_mangen::Man::new(command)
+            .render(&mut page)
+            .context("Failed to render the man page")?;
+        print!("{}", String::from_utf8_lossy(&page));
+        return Ok(());
+    };
+
+    let out_dir = PathBuf::from(out_dir);
+    fs::create_dir_all(&out_dir).context("Failed to create man page directory")?;
+    clap_mangen::generate_to(command, &out_dir).context("Failed to write the man pages")?;
+
+    output::set_result(serde_json::json!({ "dir": out_dir.display().to_string() }));
+    println!(
+        "{} Man pages written to: {}",
+        "✓".green().bold(),
+        out_dir.display().to_string().bright_cyan()
+    );
+    println!(
+        "\n{} Read them with: {}",
+        "Tip:".bright_blue(),
+        format!("man {}", out_dir.join("animaforge.1").display()).bright_cyan()
+    );
+    Ok(())
+}
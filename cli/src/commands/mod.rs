@@ -1,5 +1,16 @@
 pub mod create;
+pub mod doctor;
+pub mod init;
 pub mod render;
+pub mod preview;
+pub mod batch;
+pub mod stitch;
+pub mod validate;
+pub mod template;
+pub mod test;
 pub mod config;
+pub mod models;
 pub mod publish;
 pub mod search;
+pub mod completions;
+pub mod man;
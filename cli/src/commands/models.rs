@@ -0,0 +1,221 @@
+use anyhow::Result;
+use colored::Colorize;
+use indicatif::{HumanBytes, ProgressBar};
+use serde_json::json;
+
+use crate::cli::ModelsAction;
+use crate::config::Config;
+use crate::llm::ollama::{OllamaClient, PullProgress};
+use crate::utils::{output, progress};
+
+
+pub async fn execute(action: ModelsAction) -> Result<()> {
+    let config = Config::load()?;
+    let client = OllamaClient::new(config.ollama_endpoint());
+
+    match action {
+        ModelsAction::List => list_models(&client, &config).await,
+        ModelsAction::Pull { name } => pull_model(&client, &name).await,
+        ModelsAction::Show { name } => {
+            show_model(&client, name.as_deref().unwrap_or(&config.model)).await
+        }
+        ModelsAction::Use { name } => use_model(&client, config, name).await,
+    }
+}
+
+async fn list_models(client: &OllamaClient, config: &Config) -> Result<()> {
+    let models = client.list_model_details().await?;
+    output::set_result(json!({ "current": config.model, "models": &models }));
+
+    if models.is_empty() {
+        println!("{}", "No models installed.".yellow());
+        println!(
+            "\n{} Pull one with: {}",
+            "Tip:".bright_blue(),
+            "animaforge models pull llama3".bright_cyan()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "  {:<32} {:>10}  {:<12} {:<8} {}",
+        "NAME".bold(),
+        "SIZE".bold(),
+        "FAMILY".bold(),
+        "PARAMS".bold(),
+        "QUANT".bold()
+    );
+    for model in &models {
+        let current = is_same_model(&model.name, &config.model);
+        let marker = if current { "*".green() } else { " ".normal() };
+        let name = if current {
+            model.name.bright_green()
+        } else {
+            model.name.bright_cyan()
+        };
+        println!(
+            "{} {:<32} {:>10}  {:<12} {:<8} {}",
+            marker,
+            name,
+            HumanBytes(model.size).to_string(),
+            model.details.family.as_deref().unwrap_or("-"),
+            model.details.parameter_size.as_deref().unwrap_or("-"),
+            model.details.quantization_level.as_deref().unwrap_or("-")
+        );
+    }
+
+    if config.backend == "ollama" {
+        println!("\n{} current model", "*".green());
+    }
+
+    Ok(())
+}
+
+async fn pull_model(client: &OllamaClient, name: &str) -> Result<()> {
+    println!("{} {}", "Pulling".bright_green().bold(), name.bright_cyan());
+
+    // One bar per layer; Ollama reports layers one after the other
+    let mut layer: Option<(String, ProgressBar)> = None;
+    let result = client
+        .pull_model(name, |update: &PullProgress| {
+            match (&update.digest, update.total) {
+                (Some(digest), Some(total)) => {
+                    if layer.as_ref().map(|(d, _)| d) != Some(digest) {
+                        if let Some((_, pb)) = layer.take() {
+                            pb.finish();
+                        }
+                        layer = Some((digest.clone(), progress::create_download_bar(total)));
+                    }
+                    if let Some((_, pb)) = &layer {
+                        pb.set_position(update.completed.unwrap_or(0));
+                    }
+                }
+                _ => {
+                    if let Some((_, pb)) = layer.take() {
+                        pb.finish();
+                    }
+                    if update.status != "success" {
+                        println!("{} {}", "→".bright_blue(), update.status);
+                    }
+                }
+            }
+        })
+        .await;
+
+    if let Some((_, pb)) = layer {
+        pb.abandon();
+    }
+    result?;
+
+    output::set_result(json!({ "model": name }));
+    println!("{} Pulled {}", "✓".green().bold(), name.bright_cyan());
+    println!(
+        "\n{} Use it with: {}",
+        "Tip:".bright_blue(),
+        format!("animaforge models use {}", name).bright_cyan()
+    );
+
+    Ok(())
+}
+
+async fn show_model(client: &OllamaClient, name: &str) -> Result<()> {
+    let spinner = progress::create_spinner("Asking Ollama...");
+    let show = client.show_model(name).await;
+    spinner.finish_and_clear();
+    let show = show?;
+    output::set_result(json!({ "model": name, "info": &show }));
+
+    println!("{}", name.bright_cyan().bold());
+    println!("────────────────────────────────────");
+    let details = &show.details;
+    for (label, value) in [
+        ("Family:", &details.family),
+        ("Parameters:", &details.parameter_size),
+        ("Quantization:", &details.quantization_level),
+        ("Format:", &details.format),
+    ] {
+        if let Some(value) = value {
+            println!("{:15} {}", label.bright_yellow(), value);
+        }
+    }
+
+    let context = show
+        .model_info
+        .iter()
+        .find(|(key, _)| key.ends_with(".context_length"))
+        .and_then(|(_, value)| value.as_u64());
+    if let Some(context) = context {
+        println!("{:15} {}", "Context:".bright_yellow(), context);
+    }
+
+    if let Some(parameters) = show.parameters.as_deref().filter(|p| !p.trim().is_empty()) {
+        println!("\n{}", "Default parameters:".bright_yellow());
+        for line in parameters.lines() {
+            println!("  {}", line.trim().bright_black());
+        }
+    }
+
+    if let Some(license) = show.license.as_deref() {
+        if let Some(first) = license.lines().map(str::trim).find(|l| !l.is_empty()) {
+            println!("\n{} {}", "License:".bright_yellow(), first.bright_black());
+        }
+    }
+
+    Ok(())
+}
+
+async fn use_model(client: &OllamaClient, mut config: Config, name: String) -> Result<()> {
+    let models = client.list_models().await?;
+    let Some(installed) = models.iter().find(|m| is_same_model(m, &name)) else {
+        anyhow::bail!(
+            "Model '{}' is not installed. Pull it with: animaforge models pull {}",
+            name,
+            name
+        );
+    };
+
+    if config.backend != "ollama" {
+        println!(
+            "{} Switching backend from {} to ollama",
+            "→".bright_blue(),
+            config.backend
+        );
+        config.backend = "ollama".to_string();
+    }
+    config.model = installed.clone();
+    config.save()?;
+
+    output::set_result(json!({ "backend": config.backend, "model": config.model }));
+    println!(
+        "{} Now using {}",
+        "✓".green().bold(),
+        config.model.bright_cyan()
+    );
+
+    Ok(())
+}
+
+/// Ollama treats `llama3` and `llama3:latest` as the same model
+pub fn is_same_model(a: &str, b: &str) -> bool {
+    let tagged = |name: &str| {
+        if name.contains(':') {
+            name.to_string()
+        } else {
+            format!("{}:latest", name)
+        }
+    };
+    tagged(a) == tagged(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_tag_is_latest() {
+        assert!(is_same_model("llama3:latest", "llama3"));
+        assert!(is_same_model("llama3", "llama3"));
+        assert!(!is_same_model("llama3:70b", "llama3"));
+        assert!(!is_same_model("phi3:mini", "phi3"));
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::render::{
+    extract_scene_name, render_with_manim, validate_input, RenderFailure, RenderLimits,
+    SandboxMode,
+};
+use crate::utils::output::{self, ErrorCode};
+use crate::utils::{ffmpeg, progress};
+
+/// Frame rate used for timestamp previews when `--fps` is not given
+const DEFAULT_PREVIEW_FPS: u32 = 15;
+
+pub async fn execute(
+    file: String,
+    at: Vec<f64>,
+    fps: Option<u32>,
+    resolution: String,
+    output: Option<String>,
+    sandbox: SandboxMode,
+) -> Result<()> {
+    println!("{}", "Rendering preview...".bright_green().bold());
+    println!("{} {}", "Input file:".bright_yellow(), file.bright_cyan());
+    println!("{} {}\n", "Resolution:".bright_yellow(), resolution.bright_cyan());
+
+    let input_path = PathBuf::from(&file);
+    validate_input(&input_path)?;
+
+    let (width, height) = parse_resolution(&resolution)?;
+
+    if let Some(t) = at.iter().find(|t| **t < 0.0) {
+        anyhow::bail!("Invalid timestamp: {}s (must not be negative)", t);
+    }
+
+    let output_dir = determine_output_dir(&input_path, output)?;
+    let stem = input_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "animation".to_string());
+
+    let mut args = vec![
+        "-ql".to_string(),
+        "-r".to_string(),
+        format!("{},{}", width, height),
+    ];
+
+    let mut outputs = Vec::new();
+
+    if at.is_empty() && fps.is_none() {
+        // Still of the final frame only, no video encoding needed
+        args.push("-s".to_string());
+        let frame_path = output_dir.join(format!("{}_preview.png", stem));
+
+        render_preview(&input_path, &frame_path, &args, sandbox)?;
+        outputs.push(frame_path);
+    } else {
+        if !at.is_empty() {
+            ffmpeg::check_ffmpeg()?;
+        }
+
+        args.push("--fps".to_string());
+        args.push(fps.unwrap_or(DEFAULT_PREVIEW_FPS).to_string());
+        let video_path = output_dir.join(format!("{}_preview.mp4", stem));
+
+        render_preview(&input_path, &video_path, &args, sandbox)?;
+
+        let spinner = progress::create_spinner("Extracting frames...");
+        for t in &at {
+            let frame_path = output_dir.join(frame_file_name(&stem, *t));
+            if let Err(e) = ffmpeg::extract_frame(&video_path, *t, None, &frame_path) {
+                spinner.finish_and_clear();
+                return Err(e);
+            }
+            outputs.push(frame_path);
+        }
+        spinner.finish_and_clear();
+
+        // The video is only an intermediate unless a quick cut was requested
+        if fps.is_some() {
+            outputs.insert(0, video_path);
+        } else {
+            let _ = fs::remove_file(&video_path);
+        }
+    }
+
+    output::set_result(json!({
+        "input": file,
+        "outputs": outputs.iter().map(|p| p.display().to_string()).collect::<Vec<_>>(),
+    }));
+
+    println!("\n{} Preview ready!", "✓".green().bold());
+    for path in &outputs {
+        println!(
+            "{} {}",
+            "→".bright_blue(),
+            path.display().to_string().bright_cyan()
+        );
+    }
+
+    println!(
+        "\n{} Happy with it? Run {} for the full render",
+        "Tip:".bright_blue(),
+        format!("animaforge render {} --quality high", file).bright_cyan()
+    );
+
+    Ok(())
+}
+
+fn render_preview(
+    input: &Path,
+    output: &Path,
+    args: &[String],
+    sandbox: SandboxMode,
+) -> Result<()> {
+    let scene_name = extract_scene_name(input)?;
+    let spinner = progress::create_spinner("Rendering preview...");
+
+    let result = render_with_manim(
+        input,
+        &scene_name,
+        output,
+        args,
+        &RenderLimits::sandboxed(sandbox),
+        &spinner,
+    );
+    spinner.finish_and_clear();
+
+    if let Err(e) = result {
+        if let Some(failure) = e.downcast_ref::<RenderFailure>() {
+            eprintln!("{}\n", failure.report());
+            return Err(output::failure(
+                ErrorCode::RenderFailed,
+                "Preview rendering failed",
+            ));
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Parse a preview resolution like "480p" into manim's width and height
+fn parse_resolution(resolution: &str) -> Result<(u32, u32)> {
+    match resolution {
+        "480p" => Ok((854, 480)),
+        "720p" => Ok((1280, 720)),
+        "1080p" => Ok((1920, 1080)),
+        _ => anyhow::bail!("Invalid resolution. Use: 480p, 720p, or 1080p"),
+    }
+}
+
+fn frame_file_name(stem: &str, timestamp: f64) -> String {
+    format!("{}_preview_{}s.png", stem, timestamp)
+}
+
+fn determine_output_dir(input: &Path, output: Option<String>) -> Result<PathBuf> {
+    let dir = match output {
+        Some(dir) => PathBuf::from(dir),
+        // Default: next to the input file
+        None => input.parent().unwrap_or(Path::new(".")).to_path_buf(),
+    };
+
+    fs::create_dir_all(&dir).context("Failed to create output directory")?;
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolution() {
+        assert_eq!(parse_resolution("480p").unwrap(), (854, 480));
+        assert_eq!(parse_resolution("1080p").unwrap(), (1920, 1080));
+        assert!(parse_resolution("4k").is_err());
+    }
+
+    #[test]
+    fn test_frame_file_name() {
+        assert_eq!(frame_file_name("intro", 1.5), "intro_preview_1.5s.png");
+        assert_eq!(frame_file_name("intro", 3.0), "intro_preview_3s.png");
+    }
+}
@@ -6,7 +6,8 @@ use std::fs;
 use std::path::Path;
 
 use crate::config::Config;
-use crate::utils::progress;
+use crate::sidecar::Sidecar;
+use crate::utils::{output, progress};
 
 #[derive(Debug, Serialize)]
 struct PublishRequest {
@@ -14,9 +15,12 @@ struct PublishRequest {
     description: String,
     tags: Vec<String>,
     code: String,
+    /// Video length in seconds, from the last render
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<f32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct PublishResponse {
     id: String,
     url: String,
@@ -66,6 +70,20 @@ pub async fn execute(file: String) -> Result<()> {
     println!("  Title: {}", title.bright_cyan());
     println!("  Description: {}", description.bright_cyan());
     println!("  Tags: {}", tags.join(", ").bright_cyan());
+
+    // Describe the last render, if any
+    let artifacts = Sidecar::load(file_path)?.and_then(|s| s.render);
+    if let Some(info) = artifacts.as_ref().and_then(|a| a.media.as_ref()) {
+        println!("  Video: {}", info.summary().bright_cyan());
+    }
+    // The marketplace has no upload endpoint yet, and a local path is no use to others
+    if let Some(thumbnail) = artifacts.as_ref().and_then(|a| a.thumbnail.as_deref()) {
+        println!(
+            "  Thumbnail: {} {}",
+            thumbnail.bright_cyan(),
+            "(not uploaded: the marketplace doesn't accept files yet)".bright_black()
+        );
+    }
     println!();
 
     let confirmed = Confirm::new()
@@ -75,6 +93,7 @@ pub async fn execute(file: String) -> Result<()> {
 
     if !confirmed {
         println!("{}", "Publishing cancelled.".yellow());
+        output::set_result(serde_json::json!({ "published": false }));
         return Ok(());
     }
 
@@ -87,6 +106,10 @@ pub async fn execute(file: String) -> Result<()> {
         description,
         tags,
         code,
+        duration: artifacts
+            .as_ref()
+            .and_then(|a| a.media.as_ref())
+            .map(|m| m.duration_secs as f32),
     };
 
     // Upload with progress
@@ -96,6 +119,8 @@ pub async fn execute(file: String) -> Result<()> {
 
     pb.finish_with_message(format!("{} Upload complete!", "✓".green()));
 
+    output::set_result(&result);
+
     // Display result
     println!("\n{}", "✨ Animation published successfully!".bright_green().bold());
     println!("{} Animation ID: {}", "→".bright_blue(), result.id.bright_cyan());
@@ -113,10 +138,7 @@ async fn upload_to_marketplace(
     config: &Config,
     data: PublishRequest,
 ) -> Result<PublishResponse> {
-    let marketplace_url = std::env::var("MARKETPLACE_API_URL")
-        .unwrap_or_else(|_| "http://localhost:8000".to_string());
-
-    let url = format!("{}/api/animations", marketplace_url);
+    let url = format!("{}/api/animations", config.marketplace_url());
 
     let client = reqwest::Client::new();
 
@@ -124,7 +146,7 @@ async fn upload_to_marketplace(
         .json(&data);
 
     // Add auth token if available
-    if let Some(token) = &config.marketplace_token {
+    if let Some(token) = config.marketplace_token()? {
         request = request.header("Authorization", format!("Bearer {}", token));
     }
 
@@ -141,7 +163,7 @@ async fn upload_to_marketplace(
             "Marketplace API error ({}): {}\n\nNote: Make sure the marketplace API is running on {}",
             status,
             error_text,
-            marketplace_url
+            config.marketplace_url()
         );
     }
 
@@ -1,48 +1,290 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::json;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
-use std::io::{BufRead, BufReader};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-pub async fn execute(file: String, quality: String, output: Option<String>) -> Result<()> {
-    println!("{}", "Rendering animation...".bright_green().bold());
-    println!("{} {}", "Input file:".bright_yellow(), file.bright_cyan());
-    println!("{} {}\n", "Quality:".bright_yellow(), quality.bright_cyan());
+use crate::commands::batch;
+use crate::commands::stitch::{self, StitchOptions};
+use crate::config::{Config, RenderTarget, ThumbnailConfig};
+use crate::engine::{self, protocol::{EngineError, Message, Request}};
+use crate::manim::log::RenderLog;
+use crate::manim::target as manim_target;
+use crate::manim::progress::{self as manim_progress, RenderProgress};
+use crate::manim::traceback::{self, Traceback};
+use crate::media::check::{self, CheckOptions, CheckReport, Verdict};
+use crate::media::{self, mux::MuxOptions};
+use crate::sidecar::{self, RenderArtifacts};
+use crate::utils::output::{self, ErrorCode};
+use crate::utils::{process, progress, sandbox};
 
-    // Validate input file exists
-    let input_path = PathBuf::from(&file);
-    if !input_path.exists() {
-        anyhow::bail!("Animation file not found: {}", file);
+/// Everything `render` was asked to do besides which file to render
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub quality: String,
+    /// Output file, or output directory in batch mode
+    pub output: Option<String>,
+    /// Parallel renders in batch mode
+    pub jobs: usize,
+    pub limits: RenderLimits,
+    pub thumbnails: ThumbnailConfig,
+    /// Render every scene and join them into one video
+    pub stitch: Option<StitchOptions>,
+    /// Audio and subtitles to add to the output
+    pub mux: MuxOptions,
+    /// Name and settings of the render target from `render --target`
+    pub target: Option<(String, RenderTarget)>,
+    /// Check the output frames for blank, static or cropped content
+    pub check: Option<CheckOptions>,
+}
+
+impl RenderOptions {
+    /// Options for a plain render with everything else taken from the config
+    pub fn from_config(config: &Config, quality: &str) -> Self {
+        Self {
+            quality: quality.to_string(),
+            output: None,
+            jobs: 1,
+            limits: RenderLimits::sandboxed(SandboxMode::from_config(config)),
+            thumbnails: config.thumbnails.clone(),
+            stitch: None,
+            mux: MuxOptions::default(),
+            target: None,
+            check: None,
+        }
     }
 
-    // Validate file is Python
-    if input_path.extension().and_then(|s| s.to_str()) != Some("py") {
-        anyhow::bail!("Input file must be a Python (.py) file");
+    /// Manim flags for a render: the quality preset, then the target's overrides
+    pub fn manim_args(&self, quality: &QualitySettings) -> Result<Vec<String>> {
+        let mut args = vec![quality.flag.to_string()];
+        if let Some((name, target)) = &self.target {
+            args.extend(manim_target::args(name, target)?);
+        }
+        Ok(args)
     }
 
-    // Determine quality settings
-    let quality_settings = match quality.as_str() {
-        "low" => QualitySettings {
-            resolution: "480p15",
-            flag: "-ql",
-        },
-        "medium" => QualitySettings {
-            resolution: "720p30",
-            flag: "-qm",
-        },
-        "high" => QualitySettings {
-            resolution: "1080p60",
-            flag: "-qh",
-        },
-        _ => {
-            anyhow::bail!("Invalid quality setting. Use: low, medium, or high");
+    /// Extension of the rendered file
+    pub fn extension(&self) -> &str {
+        self.target
+            .as_ref()
+            .map_or("mp4", |(_, target)| manim_target::extension(target))
+    }
+}
+
+/// Limits applied to a single manim process
+#[derive(Debug, Clone, Default)]
+pub struct RenderLimits {
+    /// Wall-clock time before the render is killed
+    pub timeout: Option<Duration>,
+    /// CPU time in seconds
+    pub cpu_secs: Option<u64>,
+    /// Address-space limit in megabytes
+    pub memory_mb: Option<u64>,
+    /// Which renders run in a sandbox
+    pub sandbox: SandboxMode,
+}
+
+impl RenderLimits {
+    /// No resource limits, only the given sandbox mode
+    pub fn sandboxed(sandbox: SandboxMode) -> Self {
+        Self { sandbox, ..Default::default() }
+    }
+}
+
+/// When to render inside a sandbox, from the `sandbox` setting
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SandboxMode {
+    Always,
+    /// Files not produced by `create`
+    Untrusted,
+    #[default]
+    Never,
+}
+
+impl SandboxMode {
+    /// The mode the config asks for. The value was checked when it was loaded.
+    pub fn from_config(config: &Config) -> Self {
+        match config.sandbox.as_deref() {
+            Some("always") => Self::Always,
+            Some("untrusted") => Self::Untrusted,
+            _ => Self::Never,
         }
-    };
+    }
+
+    fn applies_to(self, input: &Path) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Untrusted => !sidecar::is_generated(input),
+            Self::Never => false,
+        }
+    }
+}
+
+/// Why a render was stopped before manim could finish on its own
+#[derive(Debug, thiserror::Error)]
+pub enum Termination {
+    #[error("Render timed out after {0}s and was killed")]
+    Timeout(u64),
+    #[error("Render exceeded the CPU time limit of {0}s and was killed")]
+    CpuLimit(u64),
+    #[error("Render exceeded the memory limit of {0} MB")]
+    MemoryLimit(u64),
+    #[error("Render cancelled")]
+    Cancelled,
+}
+
+/// Manim exited with an error. Explains it from the render's log.
+#[derive(Debug)]
+pub struct RenderFailure {
+    input: PathBuf,
+    log: PathBuf,
+    traceback: Option<Traceback>,
+    /// Last lines of output, for failures without a traceback
+    tail: Vec<String>,
+}
+
+impl RenderFailure {
+    fn from_log(input: &Path, log: &RenderLog) -> Self {
+        let output = log.contents();
+        let tail = output
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .rev()
+            .take(10)
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+
+        Self {
+            input: input.to_path_buf(),
+            log: log.path().to_path_buf(),
+            traceback: traceback::parse(&output),
+            tail,
+        }
+    }
+
+    /// The failing line of the animation file with context, the exception
+    /// and where the full log is
+    pub fn report(&self) -> String {
+        let mut lines = Vec::new();
+
+        match &self.traceback {
+            Some(traceback) => {
+                lines.push(format!("{} {}", "✗".red().bold(), traceback.exception.red().bold()));
+
+                let user_frame = traceback.frame_in(&self.input);
+                if let Some(frame) = user_frame.or(traceback.frames.last()) {
+                    let function = frame
+                        .function
+                        .as_ref()
+                        .map(|f| format!(" in {}", f))
+                        .unwrap_or_default();
+                    lines.push(format!(
+                        "  {} {}:{}{}",
+                        "-->".bright_blue(),
+                        frame.file,
+                        frame.line,
+                        function
+                    ));
+                }
+
+                let source = user_frame.and_then(|_| fs::read_to_string(&self.input).ok());
+                if let (Some(frame), Some(source)) = (user_frame, source) {
+                    let width = (frame.line + 2).to_string().len();
+                    for (number, text) in traceback::snippet(&source, frame.line, 2) {
+                        let gutter = format!("{:>width$} |", number, width = width);
+                        if number == frame.line {
+                            lines.push(format!("  {} {} {}", ">".red().bold(), gutter.bright_blue(), text));
+                        } else {
+                            lines.push(format!("    {} {}", gutter.bright_blue(), text.dimmed()));
+                        }
+                    }
+                }
+            }
+            None => {
+                lines.push(format!("{} Manim exited with an error. Last output:", "✗".red().bold()));
+                lines.extend(self.tail.iter().map(|line| format!("  {}", line.dimmed())));
+            }
+        }
+
+        lines.push(format!(
+            "{} Full manim log: {}",
+            "→".bright_blue(),
+            self.log.display().to_string().bright_cyan()
+        ));
+        lines.join("\n")
+    }
+}
+
+impl std::fmt::Display for RenderFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Rendering failed")?;
+        if let Some(traceback) = &self.traceback {
+            write!(f, ": {}", traceback.exception)?;
+            if let Some(frame) = traceback.frame_in(&self.input) {
+                write!(f, " (line {})", frame.line)?;
+            }
+        }
+        write!(f, ". Log: {}", self.log.display())
+    }
+}
+
+impl std::error::Error for RenderFailure {}
+
+pub async fn execute(file: String, options: RenderOptions) -> Result<()> {
+    // Directories and glob patterns render as a batch
+    if batch::is_batch_input(&file) {
+        if options.stitch.is_some() {
+            anyhow::bail!("--stitch renders the scenes of a single file, not a batch");
+        }
+        if !options.mux.is_empty() {
+            anyhow::bail!("--audio and --subtitles apply to a single file, not a batch");
+        }
+        if options.check.is_some() {
+            anyhow::bail!("--check applies to a single file, not a batch");
+        }
+        let inputs = batch::resolve_inputs(&file)?;
+        return batch::execute(inputs, &options);
+    }
+
+    let quality = &options.quality;
+
+    println!("{}", "Rendering animation...".bright_green().bold());
+    println!("{} {}", "Input file:".bright_yellow(), file.bright_cyan());
+
+    if let Some((name, target)) = &options.target {
+        println!(
+            "{} {} ({})",
+            "Target:".bright_yellow(),
+            name.bright_cyan(),
+            manim_target::summary(target)
+        );
+    }
+
+    // Determine quality settings
+    let quality_settings = QualitySettings::from_name(quality)?;
+    println!(
+        "{} {} ({})\n",
+        "Quality:".bright_yellow(),
+        quality.bright_cyan(),
+        quality_settings.resolution
+    );
+
+    // Validate input file
+    let input_path = PathBuf::from(&file);
+    validate_input(&input_path)?;
 
     // Determine output path
-    let output_path = determine_output_path(&input_path, output)?;
+    let output_path =
+        determine_output_path(&input_path, options.output.clone(), options.extension())?;
+    options.mux.validate(&output_path)?;
 
     println!(
         "{} Rendering to: {}\n",
@@ -50,8 +292,12 @@ pub async fn execute(file: String, quality: String, output: Option<String>) -> R
         output_path.display().to_string().bright_cyan()
     );
 
+    if let Some(stitch) = &options.stitch {
+        return stitch::execute(&input_path, &output_path, &quality_settings, stitch, &options);
+    }
+
     // Create progress bar
-    let pb = ProgressBar::new(100);
+    let pb = progress::new_bar(100);
     pb.set_style(
         ProgressStyle::default_bar()
             .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}% {msg}")
@@ -60,101 +306,661 @@ pub async fn execute(file: String, quality: String, output: Option<String>) -> R
     );
 
     // Call Python engine to render
-    let result = render_with_manim(&input_path, &output_path, &quality_settings, &pb)?;
+    let scene_name = extract_scene_name(&input_path)?;
+    let args = options.manim_args(&quality_settings)?;
+    let result = render_with_manim(
+        &input_path,
+        &scene_name,
+        &output_path,
+        &args,
+        &options.limits,
+        &pb,
+    );
+
+    if let Err(e) = result {
+        if e.downcast_ref::<Termination>().is_some() {
+            pb.abandon_with_message(format!("{} Render terminated", "✗".red()));
+        }
+        if let Some(failure) = e.downcast_ref::<RenderFailure>() {
+            pb.abandon_with_message(format!("{} Rendering failed", "✗".red()));
+            eprintln!("\n{}\n", failure.report());
+            return Err(output::with_code(ErrorCode::RenderFailed, e));
+        }
+        return Err(e);
+    }
 
     pb.finish_with_message("Rendering complete!".to_string());
 
-    if result {
-        println!(
-            "\n{} Animation rendered successfully!",
-            "✓".green().bold()
-        );
-        println!(
-            "{} Output: {}",
-            "→".bright_blue(),
-            output_path.display().to_string().bright_cyan()
-        );
-    } else {
-        anyhow::bail!("Rendering failed");
+    println!(
+        "\n{} Animation rendered successfully!",
+        "✓".green().bold()
+    );
+    println!(
+        "{} Output: {}",
+        "→".bright_blue(),
+        output_path.display().to_string().bright_cyan()
+    );
+
+    let mut warnings = Vec::new();
+    if !options.mux.is_empty() {
+        let spinner = progress::create_spinner("Adding audio and subtitles...");
+        warnings = media::mux::apply(&output_path, &options.mux)?;
+        spinner.finish_and_clear();
+    }
+
+    let spinner = progress::create_spinner("Inspecting video and making thumbnails...");
+    let (artifacts, media_warnings) =
+        media::process_render(&input_path, &output_path, &options.thumbnails)?;
+    spinner.finish_and_clear();
+    warnings.extend(media_warnings);
+
+    if let Some(info) = &artifacts.media {
+        println!("{} Video: {}", "→".bright_blue(), info.summary().bright_cyan());
+    }
+    for path in artifacts.thumbnail.iter().chain(artifacts.preview_gif.iter()) {
+        println!("{} Thumbnail: {}", "→".bright_blue(), path.bright_cyan());
+    }
+    for warning in &warnings {
+        println!("{} {}", "⚠".yellow(), warning.yellow());
+    }
+
+    record_render(&input_path, &artifacts, warnings, &options)
+}
+
+/// Record the result of a single render, running `--check` if asked
+pub fn record_render(
+    input: &Path,
+    artifacts: &RenderArtifacts,
+    warnings: Vec<String>,
+    options: &RenderOptions,
+) -> Result<()> {
+    let mut result = json!({
+        "input": input.display().to_string(),
+        "output": artifacts.video,
+        "quality": options.quality,
+        "target": options.target.as_ref().map(|(name, _)| name),
+        "artifacts": artifacts,
+        "warnings": warnings,
+    });
+    output::set_result(&result);
+
+    if let Some(check) = &options.check {
+        let report = quality_check(Path::new(&artifacts.video), check)?;
+        result["check"] = serde_json::to_value(&report)?;
+        output::set_result(&result);
+        if report.verdict() == Verdict::Fail {
+            return Err(output::failure(
+                ErrorCode::CheckFailed,
+                "The rendered video failed the quality check",
+            ));
+        }
     }
 
     Ok(())
 }
 
-struct QualitySettings {
-    resolution: &'static str,
-    flag: &'static str,
+/// Run `render --check` on a finished video; warnings are only reported
+pub fn quality_check(video: &Path, options: &CheckOptions) -> Result<CheckReport> {
+    let spinner = progress::create_spinner("Checking frames...");
+    let report = check::run(video, options);
+    spinner.finish_and_clear();
+
+    let report = report?;
+    report.print();
+    Ok(report)
 }
 
-fn render_with_manim(
-    input: &Path,
-    output: &Path,
-    quality: &QualitySettings,
-    pb: &ProgressBar,
-) -> Result<bool> {
-    pb.set_message("Initializing render...");
+pub struct QualitySettings {
+    pub resolution: &'static str,
+    pub flag: &'static str,
+}
 
-    // Check if manim is available
+impl QualitySettings {
+    pub fn from_name(quality: &str) -> Result<Self> {
+        match quality {
+            "low" => Ok(QualitySettings {
+                resolution: "480p15",
+                flag: "-ql",
+            }),
+            "medium" => Ok(QualitySettings {
+                resolution: "720p30",
+                flag: "-qm",
+            }),
+            "high" => Ok(QualitySettings {
+                resolution: "1080p60",
+                flag: "-qh",
+            }),
+            _ => {
+                anyhow::bail!("Invalid quality setting. Use: low, medium, or high");
+            }
+        }
+    }
+}
+
+/// Check that the input exists and is a Python file
+pub fn validate_input(input_path: &Path) -> Result<()> {
+    if !input_path.exists() {
+        anyhow::bail!("Animation file not found: {}", input_path.display());
+    }
+
+    if input_path.extension().and_then(|s| s.to_str()) != Some("py") {
+        anyhow::bail!("Input file must be a Python (.py) file");
+    }
+
+    Ok(())
+}
+
+/// Check that the manim executable is on PATH
+pub fn check_manim() -> Result<()> {
     let manim_check = Command::new("manim")
         .arg("--version")
         .output();
 
     if manim_check.is_err() {
-        pb.finish_with_message("Manim not found!".to_string());
         anyhow::bail!(
             "Manim is not installed. Install it with: pip install manim\n\
              Or use the Python engine: cd ../engine && pip install -e ."
         );
     }
 
-    // Extract the scene name from the Python file
-    let scene_name = extract_scene_name(input)?;
+    Ok(())
+}
+
+/// Render `scene_name` from `input` with manim and move the result to `output`.
+///
+/// The render goes through the Python engine when it is installed and calls
+/// manim directly otherwise. `args` are passed to manim before the file and
+/// scene name, e.g. a quality flag or `-s` to save only the last frame.
+/// Manim's full output goes to a [`RenderLog`]. Fails with a
+/// [`RenderFailure`] when manim itself fails and a [`Termination`] error
+/// when the process was killed.
+pub fn render_with_manim(
+    input: &Path,
+    scene_name: &str,
+    output: &Path,
+    args: &[String],
+    limits: &RenderLimits,
+    pb: &ProgressBar,
+) -> Result<()> {
+    pb.set_message("Initializing render...");
+
+    if let Err(e) = check_manim() {
+        pb.finish_with_message("Manim not found!".to_string());
+        return Err(e);
+    }
 
     // Prepare output directory
     let output_dir = output.parent().unwrap_or(Path::new("."));
     fs::create_dir_all(output_dir)
         .context("Failed to create output directory")?;
 
-    pb.set_message(format!("Rendering scene: {}", scene_name));
-
-    // Run manim render command
-    let mut child = Command::new("manim")
-        .arg("render")
-        .arg(quality.flag)
-        .arg(input)
-        .arg(&scene_name)
-        .arg("-o")
-        .arg(output.file_name().unwrap())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .context("Failed to start manim render")?;
-
-    // Read output and update progress
-    if let Some(stderr) = child.stderr.take() {
-        let reader = BufReader::new(stderr);
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                // Parse progress from manim output
-                if line.contains("%") {
-                    if let Some(percent) = extract_percentage(&line) {
-                        pb.set_position(percent);
+    // Manim nests its output under <media_dir>/videos/<module>/<quality>/,
+    // so render into a scratch media directory and move the file afterwards
+    let mut media_dir = media_dir_for(output);
+    let file_name = output.file_name().context("Output path has no file name")?;
+    let mut input = input.to_path_buf();
+
+    let sandboxed = limits.sandbox.applies_to(&input);
+
+    let estimated = fs::read_to_string(&input)
+        .map(|source| manim_progress::estimate_animations(&source))
+        .unwrap_or(1);
+    let progress = Arc::new(Mutex::new(RenderProgress::new(estimated)));
+    let log = Arc::new(RenderLog::create(&input, scene_name)?);
+    let use_engine = engine::available();
+
+    // Unsandboxed renders go to the long-lived engine worker when possible
+    #[cfg(unix)]
+    if use_engine && !sandboxed && engine::worker::enabled() {
+        pb.set_message(format!("Rendering scene: {}", scene_name));
+        let params = render_params(&input, scene_name, &media_dir, args, file_name, limits);
+        let run = render_in_worker(params, limits, pb, &progress, &log)?;
+        return finish_render(run, &input, &log, &media_dir, file_name, output, limits);
+    }
+
+    let program = if use_engine { engine::python() } else { "manim".to_string() };
+
+    let mut command = if sandboxed {
+        sandbox::check_available()?;
+
+        // The sandbox only sees absolute paths that exist up front
+        fs::create_dir_all(&media_dir).context("Failed to create media directory")?;
+        media_dir = fs::canonicalize(&media_dir)?;
+        input = fs::canonicalize(&input)?;
+        let input_dir = input.parent().unwrap_or(Path::new("/"));
+
+        pb.set_message(format!("Rendering scene: {} (sandboxed)", scene_name));
+        sandbox::command(&program, &[input_dir], &[&media_dir], input_dir)?
+    } else {
+        pb.set_message(format!("Rendering scene: {}", scene_name));
+        Command::new(&program)
+    };
+
+    if use_engine {
+        // The render request is written to stdin once the engine is running
+        engine::configure(&mut command);
+        command.args(engine::args()).stdin(Stdio::piped());
+    } else {
+        command
+            .arg("render")
+            .args(args)
+            .arg(&input)
+            .arg(scene_name)
+            .arg("-o")
+            .arg(file_name)
+            .arg("--media_dir")
+            .arg(&media_dir);
+    }
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    process::isolate(&mut command, limits.cpu_secs, limits.memory_mb);
+
+    let mut child = command.spawn().context("Failed to start manim render")?;
+    let pid = child.id();
+    process::register(pid);
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let request = Request {
+            id: 1,
+            method: "render",
+            params: render_params(&input, scene_name, &media_dir, args, file_name, limits),
+        };
+        // A failed write means the engine already exited; that shows up below
+        let _ = stdin.write_all(request.to_line().as_bytes());
+    }
+
+    // Drain both pipes so manim never blocks on a full one; tqdm bars arrive
+    // on stderr and the "partial movie file" log lines on stdout
+    let answer: Arc<Mutex<Option<Result<PathBuf, EngineError>>>> = Arc::new(Mutex::new(None));
+
+    // With the engine, stdout carries protocol messages wrapping manim's output
+    let readers = [
+        child.stdout.take().map(|s| (Box::new(s) as Box<dyn std::io::Read + Send>, use_engine)),
+        child.stderr.take().map(|s| (Box::new(s) as Box<dyn std::io::Read + Send>, false)),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|(stream, protocol)| {
+        let progress = Arc::clone(&progress);
+        let answer = Arc::clone(&answer);
+        let log = Arc::clone(&log);
+        let pb = pb.clone();
+        std::thread::spawn(move || {
+            let mut out_of_memory = false;
+            manim_progress::for_each_line(stream, |line| {
+                let line = if protocol {
+                    match engine_message(line, &pb, &answer) {
+                        Some(output) => output,
+                        None => return,
+                    }
+                } else {
+                    line.to_string()
+                };
+                out_of_memory |= track_output(&line, &progress, &log, &pb);
+            });
+            out_of_memory
+        })
+    })
+    .collect::<Vec<_>>();
+
+    let cpu_before = process::children_cpu_time();
+    let waited = wait_with_timeout(&mut child, limits.timeout);
+    process::unregister(pid);
+    let (status, timed_out) = waited?;
+    // Includes other renders reaped meanwhile, which only matters for a SIGKILL
+    let cpu_time = process::children_cpu_time()
+        .zip(cpu_before)
+        .map(|(after, before)| after.saturating_sub(before));
+    // Join every reader (no short-circuiting) before touching the output
+    let out_of_memory = readers
+        .into_iter()
+        .map(|reader| reader.join().unwrap_or(false))
+        .collect::<Vec<_>>()
+        .contains(&true);
+
+    let answer = match answer.lock().unwrap().take() {
+        _ if !use_engine => RenderAnswer::Manim,
+        Some(answer) => RenderAnswer::Engine(answer),
+        None => RenderAnswer::Missing,
+    };
+    let run = RenderRun { status, timed_out, out_of_memory, cpu_time, answer };
+
+    finish_render(run, &input, &log, &media_dir, file_name, output, limits)
+}
+
+/// How a render process ended
+struct RenderRun {
+    status: ExitStatus,
+    timed_out: bool,
+    out_of_memory: bool,
+    /// CPU time the render used, when known
+    cpu_time: Option<Duration>,
+    answer: RenderAnswer,
+}
+
+enum RenderAnswer {
+    /// manim ran directly; its output has to be found in the media directory
+    Manim,
+    /// The engine's result or error
+    Engine(Result<PathBuf, EngineError>),
+    /// The engine exited without answering
+    Missing,
+}
+
+/// Turn a finished render into the file at `output`, or the reason there is none
+fn finish_render(
+    run: RenderRun,
+    input: &Path,
+    log: &RenderLog,
+    media_dir: &Path,
+    file_name: &std::ffi::OsStr,
+    output: &Path,
+    limits: &RenderLimits,
+) -> Result<()> {
+    if let Some(reason) = termination_reason(&run, limits) {
+        let _ = fs::remove_dir_all(media_dir);
+        return Err(reason.into());
+    }
+
+    let rendered = match run.answer {
+        RenderAnswer::Engine(Ok(path)) => path,
+        RenderAnswer::Engine(Err(e)) if e.code == "render_failed" => {
+            return Err(RenderFailure::from_log(input, log).into());
+        }
+        RenderAnswer::Engine(Err(e)) => return Err(e.into()),
+        RenderAnswer::Missing => {
+            anyhow::bail!("The Python engine exited ({}) without a result", run.status)
+        }
+        RenderAnswer::Manim => {
+            if !run.status.success() {
+                return Err(RenderFailure::from_log(input, log).into());
+            }
+
+            find_rendered_file(media_dir, file_name)
+                .context("Manim finished but the rendered file could not be found")?
+        }
+    };
+    fs::rename(&rendered, output)
+        .or_else(|_| fs::copy(&rendered, output).map(|_| ()))
+        .context("Failed to move rendered file to output path")?;
+    let _ = fs::remove_dir_all(media_dir);
+
+    Ok(())
+}
+
+/// Parameters of an engine `render` request
+fn render_params(
+    input: &Path,
+    scene_name: &str,
+    media_dir: &Path,
+    args: &[String],
+    file_name: &std::ffi::OsStr,
+    limits: &RenderLimits,
+) -> serde_json::Value {
+    // The worker runs in its own working directory, so paths are made absolute
+    // and renders run from ours to pick up the project's manim.cfg
+    let absolute = |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+
+    json!({
+        "file": absolute(input),
+        "scene": scene_name,
+        "media_dir": absolute(media_dir),
+        "args": args,
+        "output_name": file_name.to_string_lossy(),
+        "cwd": std::env::current_dir().ok(),
+        "limits": {
+            "cpu_secs": limits.cpu_secs,
+            "memory_mb": limits.memory_mb,
+        },
+    })
+}
+
+/// Log one line of manim output and feed it to the progress bar. Returns
+/// whether the line reports that manim ran out of memory.
+fn track_output(
+    line: &str,
+    progress: &Mutex<RenderProgress>,
+    log: &RenderLog,
+    pb: &ProgressBar,
+) -> bool {
+    log.line(line);
+    if let Some(event) = manim_progress::parse_line(line) {
+        let mut progress = progress.lock().unwrap();
+        progress.update(&event);
+        pb.set_position(progress.percent());
+        pb.set_message(progress.message());
+    }
+
+    line.contains("MemoryError")
+}
+
+/// Render through the engine worker, restarting it once if it dies mid-render.
+/// The worker reports the render's process group, so timeouts and Ctrl-C
+/// reach it the same way they reach a local manim process.
+#[cfg(unix)]
+fn render_in_worker(
+    params: serde_json::Value,
+    limits: &RenderLimits,
+    pb: &ProgressBar,
+    progress: &Mutex<RenderProgress>,
+    log: &RenderLog,
+) -> Result<RenderRun> {
+    use engine::worker::{Worker, WorkerLost};
+    use std::os::unix::process::ExitStatusExt;
+
+    let deadline = limits.timeout.map(|t| Instant::now() + t);
+    let mut restarted = false;
+
+    loop {
+        let mut worker = Worker::connect()?;
+        let mut render_pid = None;
+        let mut timed_out = false;
+        let mut out_of_memory = false;
+
+        let outcome = worker.send("render", params.clone()).and_then(|_| loop {
+            if !timed_out && deadline.is_some_and(|d| Instant::now() >= d) {
+                timed_out = true;
+                if let Some(pid) = render_pid {
+                    process::kill_group(pid);
+                }
+            }
+
+            match worker.next_message(Some(Duration::from_millis(100)))? {
+                Some(Message::Progress { pid: Some(pid), .. }) => {
+                    render_pid = Some(pid);
+                    process::register(pid);
+                    if timed_out {
+                        process::kill_group(pid);
                     }
                 }
-                pb.set_message(line.trim().to_string());
+                Some(Message::Progress { message: Some(message), .. }) => pb.set_message(message),
+                Some(Message::Output { line, .. }) => {
+                    out_of_memory |= track_output(&line, progress, log, pb);
+                }
+                Some(Message::Result { result, .. }) => {
+                    break Ok(engine::result_path(&result).map(PathBuf::from));
+                }
+                Some(Message::Error { error, .. }) => break Ok(Err(error)),
+                _ => {}
+            }
+        });
+        if let Some(pid) = render_pid {
+            process::unregister(pid);
+            // A render orphaned by a crashed worker would otherwise keep running
+            if outcome.is_err() {
+                process::kill_group(pid);
             }
         }
+
+        match outcome {
+            Ok(answer) => {
+                // Exit code of the render, negative when a signal killed it
+                let returncode = match &answer {
+                    Ok(_) => 0,
+                    Err(e) => e.details["returncode"].as_i64().unwrap_or(1),
+                };
+                let status = if returncode < 0 {
+                    ExitStatus::from_raw(-returncode as i32)
+                } else {
+                    ExitStatus::from_raw((returncode as i32) << 8)
+                };
+                let cpu_time = match &answer {
+                    Ok(_) => None,
+                    Err(e) => e.details["cpu_secs"].as_f64().map(Duration::from_secs_f64),
+                };
+
+                return Ok(RenderRun {
+                    status,
+                    timed_out,
+                    out_of_memory,
+                    cpu_time,
+                    answer: RenderAnswer::Engine(answer),
+                });
+            }
+            Err(e) if e.is::<WorkerLost>() && !restarted && !timed_out && !process::is_cancelled() => {
+                restarted = true;
+                pb.set_message("Engine worker stopped, restarting...");
+            }
+            Err(e) => return Err(e),
+        }
     }
+}
+
+/// Handle one protocol message from the engine during a render. Returns the
+/// manim output line it carried, if any.
+fn engine_message(
+    line: &str,
+    pb: &ProgressBar,
+    answer: &Mutex<Option<Result<PathBuf, EngineError>>>,
+) -> Option<String> {
+    let outcome = match Message::parse(line) {
+        Ok(Message::Output { line, .. }) => return Some(line),
+        Ok(Message::Progress { message, .. }) => {
+            if let Some(message) = message {
+                pb.set_message(message);
+            }
+            return None;
+        }
+        Ok(Message::Ready { protocol, .. }) => match engine::check_version(protocol) {
+            Ok(()) => return None,
+            Err(e) => Err(e),
+        },
+        Ok(Message::Result { result, .. }) => engine::result_path(&result).map(PathBuf::from),
+        Ok(Message::Error { error, .. }) => Err(error),
+        // Stray output, e.g. a warning printed while the engine imports
+        Err(_) => return Some(line.to_string()),
+    };
+
+    answer.lock().unwrap().get_or_insert(outcome);
+    None
+}
+
+/// Wait for the child, killing its process group once `timeout` has elapsed.
+/// Returns the exit status and whether the timeout fired.
+fn wait_with_timeout(
+    child: &mut std::process::Child,
+    timeout: Option<Duration>,
+) -> Result<(ExitStatus, bool)> {
+    let deadline = timeout.map(|t| Instant::now() + t);
 
-    let status = child.wait().context("Failed to wait for manim process")?;
+    loop {
+        if let Some(status) = child.try_wait().context("Failed to wait for manim process")? {
+            return Ok((status, false));
+        }
+
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            process::kill_group(child.id());
+            let status = child.wait().context("Failed to wait for manim process")?;
+            return Ok((status, true));
+        }
 
-    Ok(status.success())
+        std::thread::sleep(Duration::from_millis(50));
+    }
 }
 
-fn extract_scene_name(file: &Path) -> Result<String> {
+fn termination_reason(run: &RenderRun, limits: &RenderLimits) -> Option<Termination> {
+    let status = &run.status;
+    if run.timed_out {
+        return limits.timeout.map(|t| Termination::Timeout(t.as_secs()));
+    }
+
+    if process::is_cancelled() {
+        return Some(Termination::Cancelled);
+    }
+
+    if let Some(secs) = limits.cpu_secs {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            // The kernel sends SIGXCPU at the soft limit and SIGKILL at the hard
+            // one. A SIGKILL can also come from elsewhere, e.g. the OOM killer.
+            let used_limit = run.cpu_time.is_some_and(|t| t >= Duration::from_secs(secs));
+            match status.signal() {
+                Some(libc::SIGXCPU) => return Some(Termination::CpuLimit(secs)),
+                Some(libc::SIGKILL) if used_limit => return Some(Termination::CpuLimit(secs)),
+                _ => {}
+            }
+        }
+    }
+
+    if let Some(mb) = limits.memory_mb {
+        if run.out_of_memory && !status.success() {
+            return Some(Termination::MemoryLimit(mb));
+        }
+    }
+
+    None
+}
+
+/// Scratch media directory used by manim for a given output file
+fn media_dir_for(output: &Path) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "render".to_string());
+
+    output
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(format!(".{}_media", stem))
+}
+
+/// Search a manim media directory for a rendered file with the given name
+fn find_rendered_file(dir: &Path, file_name: &std::ffi::OsStr) -> Option<PathBuf> {
+    let entries = fs::read_dir(dir).ok()?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            // Skip manim's intermediate partial movie files
+            if path.file_name().map(|n| n == "partial_movie_files").unwrap_or(false) {
+                continue;
+            }
+            if let Some(found) = find_rendered_file(&path, file_name) {
+                return Some(found);
+            }
+        } else if path.file_name() == Some(file_name) {
+            return Some(path);
+        }
+    }
+
+    None
+}
+
+pub fn extract_scene_name(file: &Path) -> Result<String> {
+    match extract_scene_names(file)?.into_iter().next() {
+        Some(name) => Ok(name),
+        None => anyhow::bail!("No Scene class found in animation file"),
+    }
+}
+
+/// All Scene classes defined in `file`, in declaration order
+pub fn extract_scene_names(file: &Path) -> Result<Vec<String>> {
     let content = fs::read_to_string(file)
         .context("Failed to read animation file")?;
 
+    let mut names = Vec::new();
+
     // Look for class definitions that inherit from Scene
     for line in content.lines() {
         let line = line.trim();
@@ -163,36 +969,66 @@ fn extract_scene_name(file: &Path) -> Result<String> {
             if let Some(class_name) = line
                 .split_whitespace()
                 .nth(1)
-                .and_then(|s| s.split('(').next())
+                .and_then(|s| s.split(['(', ':']).next())
             {
-                return Ok(class_name.to_string());
+                names.push(class_name.to_string());
             }
         }
     }
 
-    anyhow::bail!("No Scene class found in animation file")
+    Ok(names)
 }
 
-fn extract_percentage(line: &str) -> Option<u64> {
-    // Try to find percentage in format like "50%" or "Rendering: 50%"
-    for part in line.split_whitespace() {
-        if part.ends_with('%') {
-            if let Ok(num) = part.trim_end_matches('%').parse::<u64>() {
-                return Some(num);
-            }
+/// Output file for `input`. `extension` is the format manim writes, which an
+/// explicit output path has to match.
+pub fn determine_output_path(
+    input: &Path,
+    output: Option<String>,
+    extension: &str,
+) -> Result<PathBuf> {
+    if let Some(path) = output {
+        let path = PathBuf::from(path);
+        if extension != "mp4" && path.extension().is_some_and(|ext| ext != extension) {
+            anyhow::bail!(
+                "The render target writes .{} files but the output is {}",
+                extension,
+                path.display()
+            );
         }
+        return Ok(path);
     }
-    None
+
+    // Default: same directory as input, with the format's extension
+    let mut output_path = input.to_path_buf();
+    output_path.set_extension(extension);
+
+    Ok(output_path)
 }
 
-fn determine_output_path(input: &Path, output: Option<String>) -> Result<PathBuf> {
-    if let Some(path) = output {
-        return Ok(PathBuf::from(path));
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    fn killed_by(signal: i32, cpu_secs: u64) -> RenderRun {
+        RenderRun {
+            status: ExitStatus::from_raw(signal),
+            timed_out: false,
+            out_of_memory: false,
+            cpu_time: Some(Duration::from_secs(cpu_secs)),
+            answer: RenderAnswer::Manim,
+        }
     }
 
-    // Default: same directory as input, with .mp4 extension
-    let mut output_path = input.to_path_buf();
-    output_path.set_extension("mp4");
+    #[test]
+    fn blames_cpu_limit_only_when_it_was_reached() {
+        let limits = RenderLimits { cpu_secs: Some(10), ..Default::default() };
 
-    Ok(output_path)
+        let reason = termination_reason(&killed_by(libc::SIGXCPU, 10), &limits);
+        assert!(matches!(reason, Some(Termination::CpuLimit(10))));
+        let reason = termination_reason(&killed_by(libc::SIGKILL, 11), &limits);
+        assert!(matches!(reason, Some(Termination::CpuLimit(10))));
+
+        assert!(termination_reason(&killed_by(libc::SIGKILL, 2), &limits).is_none());
+    }
 }
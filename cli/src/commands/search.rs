@@ -1,10 +1,11 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::utils::progress;
+use crate::config::Config;
+use crate::utils::{output, progress};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct SearchResult {
     id: String,
     title: String,
@@ -15,7 +16,7 @@ struct SearchResult {
     created_at: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct SearchResponse {
     results: Vec<SearchResult>,
     total: usize,
@@ -26,16 +27,19 @@ pub async fn execute(query: String, limit: usize) -> Result<()> {
     println!("{} {}", "Query:".bright_yellow(), query.bright_cyan());
     println!("{} {}\n", "Limit:".bright_yellow(), limit.to_string().bright_cyan());
 
+    let config = Config::load()?;
+
     // Search marketplace
     let spinner = progress::create_spinner("Searching...");
 
-    let results = search_marketplace(&query, limit).await?;
+    let results = search_marketplace(&config, &query, limit).await?;
 
     spinner.finish_with_message(format!(
         "{} Found {} results",
         "✓".green(),
         results.total
     ));
+    output::set_result(&results);
 
     // Display results
     if results.results.is_empty() {
@@ -84,13 +88,10 @@ pub async fn execute(query: String, limit: usize) -> Result<()> {
     Ok(())
 }
 
-async fn search_marketplace(query: &str, limit: usize) -> Result<SearchResponse> {
-    let marketplace_url = std::env::var("MARKETPLACE_API_URL")
-        .unwrap_or_else(|_| "http://localhost:8000".to_string());
-
+async fn search_marketplace(config: &Config, query: &str, limit: usize) -> Result<SearchResponse> {
     let url = format!(
         "{}/api/animations/search?q={}&limit={}",
-        marketplace_url,
+        config.marketplace_url(),
         urlencoding::encode(query),
         limit
     );
@@ -0,0 +1,271 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use indicatif::ProgressStyle;
+use std::fs;
+use std::path::Path;
+
+use crate::commands::render::{
+    self, extract_scene_names, render_with_manim, QualitySettings, RenderFailure, RenderOptions,
+};
+use crate::media::{self, probe};
+use crate::utils::output::{self, ErrorCode};
+use crate::utils::{ffmpeg, progress};
+
+/// How to combine the scenes of one file into a single video
+#[derive(Debug, Clone, Default)]
+pub struct StitchOptions {
+    /// Scene order; empty means declaration order
+    pub order: Vec<String>,
+    /// Crossfade length in seconds between scenes
+    pub crossfade: Option<f64>,
+}
+
+/// Render every scene of `input` and join them into `output`, writing a
+/// WebVTT chapters file with each scene's start time next to it
+pub fn execute(
+    input: &Path,
+    output: &Path,
+    quality: &QualitySettings,
+    stitch: &StitchOptions,
+    options: &RenderOptions,
+) -> Result<()> {
+    let scenes = scene_order(extract_scene_names(input)?, &stitch.order)?;
+    let fade = stitch.crossfade.filter(|f| *f > 0.0);
+
+    if options.extension() != "mp4" {
+        anyhow::bail!("--stitch joins mp4 clips; use a target without a format or with format = \"mp4\"");
+    }
+
+    ffmpeg::check_ffmpeg()?;
+
+    println!(
+        "{} Stitching {} scenes: {}\n",
+        "→".bright_blue(),
+        scenes.len(),
+        scenes.join(" → ").bright_cyan()
+    );
+
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "animation".to_string());
+    let scratch = output
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(format!(".{}_scenes", stem));
+    fs::create_dir_all(&scratch).context("Failed to create scene directory")?;
+
+    let joined = join_scenes(input, output, &scenes, fade, &scratch, quality, options);
+    let _ = fs::remove_dir_all(&scratch);
+    let durations = joined?;
+
+    let starts = scene_starts(&durations, fade.unwrap_or(0.0));
+    let total = starts.last().unwrap_or(&0.0) + durations.last().unwrap_or(&0.0);
+    let chapters_path = output.with_extension("chapters.vtt");
+    fs::write(&chapters_path, chapters_vtt(&scenes, &starts, total))
+        .context("Failed to write chapters file")?;
+
+    println!("\n{} Animation stitched successfully!", "✓".green().bold());
+    println!(
+        "{} Output: {}",
+        "→".bright_blue(),
+        output.display().to_string().bright_cyan()
+    );
+    println!(
+        "{} Chapters: {}",
+        "→".bright_blue(),
+        chapters_path.display().to_string().bright_cyan()
+    );
+    for (scene, start) in scenes.iter().zip(&starts) {
+        println!("    {}  {}", format_timestamp(*start).bright_black(), scene);
+    }
+
+    let mut warnings = media::mux::apply(output, &options.mux)?;
+    let (artifacts, media_warnings) = media::process_render(input, output, &options.thumbnails)?;
+    warnings.extend(media_warnings);
+    if let Some(info) = &artifacts.media {
+        println!("{} Video: {}", "→".bright_blue(), info.summary().bright_cyan());
+    }
+    for warning in &warnings {
+        println!("{} {}", "⚠".yellow(), warning.yellow());
+    }
+
+    render::record_render(input, &artifacts, warnings, options)?;
+    output::update_result(|result| {
+        result["scenes"] = serde_json::json!(scenes);
+        result["chapters"] = serde_json::json!(chapters_path.display().to_string());
+    });
+    Ok(())
+}
+
+/// Render each scene into `scratch` and join the clips into `output`.
+/// Returns the length of each scene in seconds.
+fn join_scenes(
+    input: &Path,
+    output: &Path,
+    scenes: &[String],
+    fade: Option<f64>,
+    scratch: &Path,
+    quality: &QualitySettings,
+    options: &RenderOptions,
+) -> Result<Vec<f64>> {
+    let style = ProgressStyle::default_bar()
+        .template("{spinner:.green} {prefix:.bold} [{bar:30.cyan/blue}] {pos}% {msg}")
+        .unwrap()
+        .progress_chars("#>-");
+
+    let args = options.manim_args(quality)?;
+    let mut clips = Vec::new();
+    let mut durations = Vec::new();
+
+    for (i, scene) in scenes.iter().enumerate() {
+        let pb = progress::new_bar(100);
+        pb.set_style(style.clone());
+        pb.set_prefix(format!("[{}/{}] {}", i + 1, scenes.len(), scene));
+
+        let clip = scratch.join(format!("{}.mp4", scene));
+        if let Err(e) = render_with_manim(input, scene, &clip, &args, &options.limits, &pb) {
+            pb.abandon_with_message(format!("{} failed", "✗".red()));
+            if let Some(failure) = e.downcast_ref::<RenderFailure>() {
+                eprintln!("\n{}\n", failure.report());
+                return Err(output::failure(
+                    ErrorCode::RenderFailed,
+                    format!("Scene {} failed to render", scene),
+                ));
+            }
+            return Err(e);
+        }
+        pb.finish_with_message(format!("{} done", "✓".green()));
+
+        let duration = probe::probe(&clip)
+            .context(format!("Could not read the duration of scene {}", scene))?
+            .duration_secs;
+
+        if let Some(fade) = fade {
+            if duration <= fade {
+                anyhow::bail!(
+                    "Scene {} is {:.2}s long, too short for a {:.2}s crossfade",
+                    scene,
+                    duration,
+                    fade
+                );
+            }
+        }
+
+        clips.push(clip);
+        durations.push(duration);
+    }
+
+    println!("\n{} Joining scenes...", "→".bright_blue());
+    match fade {
+        Some(fade) => ffmpeg::crossfade(&clips, &durations, fade, output)?,
+        None => ffmpeg::concat(&clips, output)?,
+    }
+
+    Ok(durations)
+}
+
+/// Apply a user-given order to the scenes found in the file
+fn scene_order(found: Vec<String>, order: &[String]) -> Result<Vec<String>> {
+    if found.is_empty() {
+        anyhow::bail!("No Scene class found in animation file");
+    }
+
+    if order.is_empty() {
+        return Ok(found);
+    }
+
+    for scene in order {
+        if !found.contains(scene) {
+            anyhow::bail!(
+                "Unknown scene in --order: {}. Scenes in this file: {}",
+                scene,
+                found.join(", ")
+            );
+        }
+    }
+
+    Ok(order.to_vec())
+}
+
+/// Start time of each scene in the joined video. A crossfade overlaps every
+/// scene after the first with the end of the one before it.
+fn scene_starts(durations: &[f64], fade: f64) -> Vec<f64> {
+    let mut start = 0.0;
+
+    durations
+        .iter()
+        .map(|duration| {
+            let this = start;
+            start += duration - fade;
+            this
+        })
+        .collect()
+}
+
+fn chapters_vtt(scenes: &[String], starts: &[f64], total: f64) -> String {
+    let mut vtt = String::from("WEBVTT\n");
+
+    for (i, (scene, start)) in scenes.iter().zip(starts).enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(total);
+        vtt.push_str(&format!(
+            "\n{}\n{} --> {}\n{}\n",
+            i + 1,
+            format_timestamp(*start),
+            format_timestamp(end),
+            scene
+        ));
+    }
+
+    vtt
+}
+
+/// Seconds as a WebVTT timestamp, e.g. `00:01:05.250`
+fn format_timestamp(secs: f64) -> String {
+    let millis = (secs.max(0.0) * 1000.0).round() as u64;
+
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_scene_order() {
+        let found = names(&["Intro", "Proof", "Outro"]);
+
+        assert_eq!(scene_order(found.clone(), &[]).unwrap(), found);
+        assert_eq!(
+            scene_order(found.clone(), &names(&["Outro", "Intro"])).unwrap(),
+            names(&["Outro", "Intro"])
+        );
+        assert!(scene_order(found, &names(&["Missing"])).is_err());
+    }
+
+    #[test]
+    fn test_scene_starts() {
+        assert_eq!(scene_starts(&[4.0, 3.0, 5.0], 0.0), vec![0.0, 4.0, 7.0]);
+        assert_eq!(scene_starts(&[4.0, 3.0, 5.0], 0.5), vec![0.0, 3.5, 6.0]);
+    }
+
+    #[test]
+    fn test_chapters_vtt() {
+        let vtt = chapters_vtt(&names(&["Intro", "Outro"]), &[0.0, 65.25], 70.0);
+
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\n1\n00:00:00.000 --> 00:01:05.250\nIntro\n\n2\n00:01:05.250 --> 00:01:10.000\nOutro\n"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::commands::render::RenderOptions;
+use crate::config::Config;
+use crate::engine;
+use crate::sidecar::{self, Sidecar};
+use crate::utils::output;
+
+pub async fn execute(
+    name: Option<String>,
+    values: Vec<String>,
+    output: Option<String>,
+    render: bool,
+) -> Result<()> {
+    let Some(name) = name else {
+        return list();
+    };
+
+    let values = parse_values(&values)?;
+    println!("{} {}", "Template:".bright_yellow(), name.bright_cyan());
+
+    let result = engine::request("template", json!({ "name": name, "values": values }), |_| {})?;
+    let code = result["code"]
+        .as_str()
+        .context("The engine returned no template code")?;
+
+    let config = Config::load()?;
+    let output_path = match output {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let output_dir = PathBuf::from(config.output_dir.as_deref().unwrap_or("./animations"));
+            fs::create_dir_all(&output_dir)
+                .context("Failed to create output directory")?;
+            output_dir.join(format!("{}.py", name))
+        }
+    };
+
+    fs::write(&output_path, code)
+        .context("Failed to write template code")?;
+
+    Sidecar::generated(&format!("template {}", name), "template", &name, code)
+        .save(&output_path)?;
+    // Template code ships with the engine, so renders treat it like generated
+    // code. `--set` values are pasted in as Python, so those files are not.
+    if values.is_empty() {
+        sidecar::trust(&output_path)?;
+    }
+
+    println!(
+        "{} Template saved to: {}",
+        "✓".green().bold(),
+        output_path.display().to_string().bright_cyan()
+    );
+
+    let mut result = json!({
+        "template": name,
+        "file": output_path.display().to_string(),
+    });
+    output::set_result(&result);
+
+    if render {
+        println!("\n{}", "Rendering template...".bright_yellow());
+        let rendered = crate::commands::render::execute(
+            output_path.to_string_lossy().to_string(),
+            RenderOptions::from_config(&config, config.quality()),
+        )
+        .await;
+        result["render"] = output::take_result().unwrap_or_default();
+        output::set_result(&result);
+        rendered?;
+    }
+
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let result = engine::request("templates", json!({}), |_| {})?;
+    let templates: Vec<String> = serde_json::from_value(result["templates"].clone())
+        .context("Unexpected template list from engine")?;
+
+    output::set_result(json!({ "templates": &templates }));
+
+    println!("{}", "Available templates:".bright_green().bold());
+    for template in templates {
+        println!("  {} {}", "•".bright_blue(), template);
+    }
+
+    Ok(())
+}
+
+/// `key=value` pairs given with `--set`
+fn parse_values(values: &[String]) -> Result<Map<String, Value>> {
+    values
+        .iter()
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) if !key.is_empty() => {
+                Ok((key.to_string(), Value::String(value.to_string())))
+            }
+            _ => anyhow::bail!("Invalid template value '{}', expected KEY=VALUE", pair),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values() {
+        let values = parse_values(&["title=Hello = World".to_string()]).unwrap();
+        assert_eq!(values["title"], "Hello = World");
+
+        assert!(parse_values(&["title".to_string()]).is_err());
+        assert!(parse_values(&["=x".to_string()]).is_err());
+    }
+}
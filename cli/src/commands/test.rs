@@ -0,0 +1,357 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::commands::batch;
+use crate::commands::render::{
+    check_manim, extract_scene_names, render_with_manim, validate_input, QualitySettings,
+    RenderFailure, RenderLimits, SandboxMode,
+};
+use crate::media::compare;
+use crate::utils::output::{self, ErrorCode};
+use crate::utils::{ffmpeg, progress};
+
+/// How `test` renders frames and judges them against their goldens
+#[derive(Debug, Clone)]
+pub struct TestOptions {
+    /// Timestamps to check; empty checks only the last frame
+    pub at: Vec<f64>,
+    pub quality: String,
+    /// Accept the rendered frames as the new goldens
+    pub update: bool,
+    /// Share of pixels in percent that may differ before a frame fails
+    pub tolerance: f64,
+    /// How far apart (0 to 1) two pixels may be before they count as different
+    pub threshold: f64,
+    /// Golden directory; default is `goldens/` next to each animation file
+    pub goldens: Option<PathBuf>,
+    /// Where the rendered frame and diff image of each failure go
+    pub results: PathBuf,
+    pub sandbox: SandboxMode,
+}
+
+/// Outcome of one frame
+enum FrameStatus {
+    Passed(f64),
+    Updated,
+    Missing,
+    Failed(f64),
+    Error(String),
+}
+
+pub async fn execute(inputs: Vec<String>, options: TestOptions) -> Result<()> {
+    if !(0.0..=1.0).contains(&options.threshold) {
+        anyhow::bail!("--threshold must be between 0 and 1");
+    }
+    if let Some(t) = options.at.iter().find(|t| **t < 0.0) {
+        anyhow::bail!("Invalid timestamp: {}s (must not be negative)", t);
+    }
+
+    let mut files = Vec::new();
+    for input in &inputs {
+        if batch::is_batch_input(input) {
+            files.extend(batch::resolve_inputs(input)?);
+        } else {
+            let path = PathBuf::from(input);
+            validate_input(&path)?;
+            files.push(path);
+        }
+    }
+
+    let quality = QualitySettings::from_name(&options.quality)?;
+
+    println!(
+        "{}",
+        "Testing animations against golden frames..."
+            .bright_green()
+            .bold()
+    );
+    println!(
+        "{} {}",
+        "Files:".bright_yellow(),
+        files.len().to_string().bright_cyan()
+    );
+    println!(
+        "{} {}\n",
+        "Frames:".bright_yellow(),
+        frame_labels(&options.at).join(", ").bright_cyan()
+    );
+
+    check_manim()?;
+    if !options.at.is_empty() {
+        ffmpeg::check_ffmpeg()?;
+    }
+
+    let scratch = std::env::temp_dir().join(format!("animaforge-test-{}", std::process::id()));
+    fs::create_dir_all(&scratch).context("Failed to create scratch directory")?;
+
+    let outcome = run(&files, &quality, &options, &scratch);
+    let _ = fs::remove_dir_all(&scratch);
+    let statuses = outcome?;
+
+    let count = |f: fn(&FrameStatus) -> bool| statuses.iter().filter(|s| f(s)).count();
+    let passed = count(|s| matches!(s, FrameStatus::Passed(_)));
+    let updated = count(|s| matches!(s, FrameStatus::Updated));
+    let failed = statuses.len() - passed - updated;
+
+    output::set_result(json!({
+        "files": files.iter().map(|f| f.display().to_string()).collect::<Vec<_>>(),
+        "passed": passed,
+        "updated": updated,
+        "failed": failed,
+        "results": options.results.display().to_string(),
+    }));
+
+    println!(
+        "\n{} {} passed, {} updated, {} failed",
+        "Summary:".bright_yellow().bold(),
+        passed.to_string().green(),
+        updated.to_string().bright_cyan(),
+        if failed > 0 {
+            failed.to_string().red()
+        } else {
+            failed.to_string().normal()
+        }
+    );
+
+    if failed > 0 {
+        if statuses.iter().any(|s| matches!(s, FrameStatus::Failed(_))) {
+            println!(
+                "{} Rendered frames and diffs: {}",
+                "→".bright_blue(),
+                options.results.display().to_string().bright_cyan()
+            );
+        }
+        println!(
+            "{} Run with {} to accept the new frames",
+            "→".bright_blue(),
+            "--update".bright_cyan()
+        );
+        return Err(output::failure(
+            ErrorCode::CheckFailed,
+            format!(
+                "{} of {} frames do not match their goldens",
+                failed,
+                statuses.len()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Render and check every scene of every file
+fn run(
+    files: &[PathBuf],
+    quality: &QualitySettings,
+    options: &TestOptions,
+    scratch: &Path,
+) -> Result<Vec<FrameStatus>> {
+    let labels = frame_labels(&options.at);
+    let mut statuses = Vec::new();
+
+    for file in files {
+        let stem = file
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "animation".to_string());
+        let goldens = match &options.goldens {
+            Some(dir) => dir.join(&stem),
+            None => file
+                .parent()
+                .unwrap_or(Path::new("."))
+                .join("goldens")
+                .join(&stem),
+        };
+
+        for scene in extract_scene_names(file)? {
+            let name = format!("{} {}", file.display(), scene);
+            let spinner = progress::create_spinner(&format!("Rendering {}...", name));
+            let frames =
+                render_frames(file, &scene, &stem, quality, options, scratch, &spinner);
+            spinner.finish_and_clear();
+
+            let frames = match frames {
+                Ok(frames) => frames,
+                Err(e) => {
+                    let Some(failure) = e.downcast_ref::<RenderFailure>() else {
+                        return Err(e);
+                    };
+                    println!("{} {}", "✗".red().bold(), name.bold());
+                    eprintln!("{}\n", failure.report());
+                    statuses.extend(
+                        labels
+                            .iter()
+                            .map(|_| FrameStatus::Error("render failed".to_string())),
+                    );
+                    continue;
+                }
+            };
+
+            println!("{}", name.bold());
+            for (label, actual) in labels.iter().zip(frames) {
+                let frame = format!("{}_{}.png", scene, label);
+                let status = check_frame(
+                    &actual,
+                    &goldens.join(&frame),
+                    &options.results.join(&stem),
+                    &frame,
+                    options,
+                );
+                print_status(label, &status);
+                statuses.push(status);
+            }
+        }
+    }
+
+    Ok(statuses)
+}
+
+/// Render one scene and return its frames in the order of `at`
+fn render_frames(
+    file: &Path,
+    scene: &str,
+    stem: &str,
+    quality: &QualitySettings,
+    options: &TestOptions,
+    scratch: &Path,
+    spinner: &indicatif::ProgressBar,
+) -> Result<Vec<PathBuf>> {
+    let at = &options.at;
+    let limits = RenderLimits::sandboxed(options.sandbox);
+    let mut args = vec![quality.flag.to_string()];
+
+    if at.is_empty() {
+        // Only the last frame, saved as an image without encoding a video
+        args.push("-s".to_string());
+        let frame = scratch.join(format!("{}_{}_last.png", stem, scene));
+        render_with_manim(
+            file,
+            scene,
+            &frame,
+            &args,
+            &limits,
+            spinner,
+        )?;
+        return Ok(vec![frame]);
+    }
+
+    let video = scratch.join(format!("{}_{}.mp4", stem, scene));
+    render_with_manim(
+        file,
+        scene,
+        &video,
+        &args,
+        &limits,
+        spinner,
+    )?;
+
+    spinner.set_message("Extracting frames...");
+    at.iter()
+        .map(|t| {
+            let frame = scratch.join(format!("{}_{}_{}s.png", stem, scene, t));
+            ffmpeg::extract_frame(&video, *t, None, &frame)?;
+            Ok(frame)
+        })
+        .collect()
+}
+
+/// Compare a rendered frame with its golden, or make it the golden
+fn check_frame(
+    actual: &Path,
+    golden: &Path,
+    results: &Path,
+    frame: &str,
+    options: &TestOptions,
+) -> FrameStatus {
+    if options.update {
+        let copied = golden
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::copy(actual, golden));
+        return match copied {
+            Ok(_) => FrameStatus::Updated,
+            Err(e) => FrameStatus::Error(format!("could not write {}: {}", golden.display(), e)),
+        };
+    }
+
+    if !golden.exists() {
+        return FrameStatus::Missing;
+    }
+
+    let comparison = compare::load_png(golden).and_then(|golden| {
+        let rendered = compare::load_png(actual)?;
+        compare::compare(&golden, &rendered, options.threshold)
+    });
+
+    match comparison {
+        Ok(comparison) if comparison.percent() <= options.tolerance => {
+            FrameStatus::Passed(comparison.percent())
+        }
+        Ok(comparison) => {
+            let stem = frame.trim_end_matches(".png");
+            let saved = fs::create_dir_all(results)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| {
+                    fs::copy(actual, results.join(format!("{}.actual.png", stem)))
+                        .map_err(Into::into)
+                })
+                .and_then(|_| {
+                    compare::save_png(
+                        &comparison.diff,
+                        &results.join(format!("{}.diff.png", stem)),
+                    )
+                });
+            match saved {
+                Ok(()) => FrameStatus::Failed(comparison.percent()),
+                Err(e) => FrameStatus::Error(format!(
+                    "{:.2}% differs, but saving the diff failed: {}",
+                    comparison.percent(),
+                    e
+                )),
+            }
+        }
+        Err(e) => FrameStatus::Error(e.to_string()),
+    }
+}
+
+fn print_status(label: &str, status: &FrameStatus) {
+    let label = format!("frame {}", label);
+    match status {
+        FrameStatus::Passed(percent) => {
+            println!("  {} {} ({:.2}% differs)", "✓".green(), label, percent)
+        }
+        FrameStatus::Updated => println!("  {} {} golden updated", "↻".bright_cyan(), label),
+        FrameStatus::Missing => println!("  {} {} has no golden yet", "✗".red(), label),
+        FrameStatus::Failed(percent) => {
+            println!(
+                "  {} {} {}",
+                "✗".red(),
+                label,
+                format!("{:.2}% of pixels differ", percent).red()
+            )
+        }
+        FrameStatus::Error(message) => println!("  {} {} {}", "✗".red(), label, message.red()),
+    }
+}
+
+/// Names of the checked frames, used in golden file names
+fn frame_labels(at: &[f64]) -> Vec<String> {
+    if at.is_empty() {
+        return vec!["last".to_string()];
+    }
+    at.iter().map(|t| format!("{}s", t)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_name_timestamps_or_last_frame() {
+        assert_eq!(frame_labels(&[]), vec!["last"]);
+        assert_eq!(frame_labels(&[0.5, 2.0]), vec!["0.5s", "2s"]);
+    }
+}
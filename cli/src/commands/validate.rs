@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::commands::render::validate_input;
+use crate::engine;
+use crate::utils::output::{self, ErrorCode};
+
+/// Report returned by the engine's `validate` method
+#[derive(Debug, Deserialize, Serialize)]
+struct ValidationReport {
+    valid: bool,
+    error: Option<String>,
+    scene_class: Option<String>,
+    #[serde(default)]
+    imports: Vec<String>,
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+pub fn execute(file: String) -> Result<()> {
+    println!("{}", "Validating animation...".bright_green().bold());
+    println!("{} {}\n", "Input file:".bright_yellow(), file.bright_cyan());
+
+    let path = PathBuf::from(&file);
+    validate_input(&path)?;
+    let path = fs::canonicalize(&path)?;
+
+    let result = engine::request("validate", json!({ "file": path }), |_| {})?;
+    let report: ValidationReport =
+        serde_json::from_value(result).context("Unexpected validation report from engine")?;
+    output::set_result(json!({ "file": path, "report": &report }));
+
+    if let Some(scene) = &report.scene_class {
+        println!("{} {}", "Scene:".bright_yellow(), scene.bright_cyan());
+    }
+    if !report.imports.is_empty() {
+        println!("{} {}", "Imports:".bright_yellow(), report.imports.join(", "));
+    }
+    for warning in &report.warnings {
+        println!("{} {}", "⚠".yellow(), warning.yellow());
+    }
+
+    if !report.valid {
+        return Err(output::failure(
+            ErrorCode::CheckFailed,
+            format!(
+                "Validation failed: {}",
+                report.error.as_deref().unwrap_or("unknown error")
+            ),
+        ));
+    }
+
+    println!("\n{} Animation code is valid", "✓".green().bold());
+
+    Ok(())
+}
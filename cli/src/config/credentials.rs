@@ -0,0 +1,280 @@
+//! Secrets like `api_key` and `marketplace_token` live in credentials.toml
+//! next to the config, readable only by the user. A secret can also be
+//! `env:VAR`, read from the environment when it is used, or encrypted with a
+//! passphrase (`enc:...`).
+
+use anyhow::{Context, Result};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use dialoguer::{theme::ColorfulTheme, Password};
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use toml::{Table, Value};
+
+use super::schema::{self, Kind};
+
+pub const FILE: &str = "credentials.toml";
+
+/// Passphrase for encrypted secrets when there is no terminal to ask on
+pub const PASSPHRASE_VAR: &str = "ANIMAFORGE_PASSPHRASE";
+
+const ENV_PREFIX: &str = "env:";
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Whether a dotted key holds a secret, also inside a profile
+pub fn is_secret(key: &str) -> bool {
+    let key = match key.strip_prefix("profiles.") {
+        Some(rest) => rest.split_once('.').map(|(_, key)| key).unwrap_or(rest),
+        None => key,
+    };
+    schema::kind(key) == Kind::Secret
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Move the secrets out of `table`
+pub fn split(table: &mut Table) -> Table {
+    let mut secrets = Table::new();
+    let keys: Vec<String> = schema::leaves(table, "")
+        .into_iter()
+        .map(|(key, _)| key)
+        .filter(|key| is_secret(key))
+        .collect();
+
+    for key in keys {
+        if let Some(value) = schema::remove(table, &key) {
+            // Keys come from leaves, so every parent is a table
+            schema::insert(&mut secrets, &key, value).ok();
+        }
+    }
+    prune(table);
+    secrets
+}
+
+/// Drop tables emptied by `split`, except top-level ones like [thumbnails]
+fn prune(table: &mut Table) {
+    if let Some(Value::Table(profiles)) = table.get_mut("profiles") {
+        profiles.retain(|_, value| !matches!(value, Value::Table(t) if t.is_empty()));
+        if profiles.is_empty() {
+            table.remove("profiles");
+        }
+    }
+}
+
+/// Read the credentials file, refusing one other users can read
+pub fn read(path: &Path) -> Result<Table> {
+    check_permissions(path)?;
+    super::layers::read_file(path)
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .permissions()
+        .mode();
+    if mode & 0o004 != 0 {
+        anyhow::bail!(
+            "{} is readable by other users, so its secrets may have leaked. \
+             Rotate them if needed, then run: chmod 600 {}",
+            path.display(),
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Write the credentials file with owner-only permissions
+pub fn write(path: &Path, secrets: &Table) -> Result<()> {
+    let content = toml::to_string_pretty(secrets).context("Failed to serialize credentials")?;
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    // `mode` only applies to new files
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// The value to use for a stored secret
+pub fn reveal(key: &str, value: &str) -> Result<String> {
+    if let Some(var) = value.strip_prefix(ENV_PREFIX) {
+        return std::env::var(var)
+            .with_context(|| format!("{} refers to ${}, which is not set", key, var));
+    }
+    if is_encrypted(value) {
+        let passphrase = passphrase(false)?;
+        return decrypt(value, &passphrase).with_context(|| format!("Can't decrypt {}", key));
+    }
+    Ok(value.to_string())
+}
+
+/// How a stored secret may be shown
+pub fn describe(value: &str) -> Option<&str> {
+    if value.starts_with(ENV_PREFIX) {
+        Some(value)
+    } else if is_encrypted(value) {
+        Some("(encrypted)")
+    } else {
+        None
+    }
+}
+
+/// Encrypt every plaintext secret; `env:` references are left alone
+pub fn encrypt_all(secrets: &mut Table, passphrase: &str) -> Result<usize> {
+    convert(secrets, |value| {
+        if value.starts_with(ENV_PREFIX) || is_encrypted(value) {
+            Ok(None)
+        } else {
+            encrypt(value, passphrase).map(Some)
+        }
+    })
+}
+
+pub fn decrypt_all(secrets: &mut Table, passphrase: &str) -> Result<usize> {
+    convert(secrets, |value| {
+        if is_encrypted(value) {
+            decrypt(value, passphrase).map(Some)
+        } else {
+            Ok(None)
+        }
+    })
+}
+
+fn convert(secrets: &mut Table, f: impl Fn(&str) -> Result<Option<String>>) -> Result<usize> {
+    let mut changed = 0;
+    let keys: Vec<(String, String)> = schema::leaves(secrets, "")
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value.as_str()?.to_string())))
+        .collect();
+
+    for (key, value) in keys {
+        if let Some(converted) = f(&value).with_context(|| format!("Can't convert {}", key))? {
+            schema::insert(secrets, &key, Value::String(converted))?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+pub fn encrypt(value: &str, passphrase: &str) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut salt)
+        .and_then(|_| getrandom::getrandom(&mut nonce))
+        .map_err(|e| anyhow::anyhow!("Failed to get random bytes: {}", e))?;
+
+    let cipher = cipher(passphrase, &salt)?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), value.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+    let mut data = salt.to_vec();
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(data)))
+}
+
+pub fn decrypt(value: &str, passphrase: &str) -> Result<String> {
+    let data = value
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .and_then(|data| STANDARD.decode(data).ok())
+        .filter(|data| data.len() > SALT_LEN + NONCE_LEN)
+        .context("Not an encrypted secret")?;
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let plaintext = cipher(passphrase, salt)?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Wrong passphrase"))?;
+    String::from_utf8(plaintext).context("Decrypted secret is not text")
+}
+
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<ChaCha20Poly1305> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Failed to derive key: {}", e))?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+/// Passphrase from ANIMAFORGE_PASSPHRASE, or asked for on the terminal.
+/// `confirm` asks twice, for setting a new one.
+pub fn passphrase(confirm: bool) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Secrets are encrypted; set {} to unlock them", PASSPHRASE_VAR);
+    }
+
+    let theme = ColorfulTheme::default();
+    let mut prompt = Password::with_theme(&theme).with_prompt("Credentials passphrase");
+    if confirm {
+        prompt = prompt.with_confirmation("Repeat passphrase", "Passphrases don't match");
+    }
+    Ok(prompt.interact()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_secrets_from_config() {
+        let mut table: Table = toml::from_str(
+            "backend = \"claude\"\napi_key = \"sk-1\"\n\
+             [profiles.cloud]\napi_key = \"sk-2\"\n\
+             [profiles.gpu]\nmodel = \"qwen\"",
+        )
+        .unwrap();
+
+        let secrets = split(&mut table);
+
+        assert_eq!(schema::get(&secrets, "api_key").and_then(Value::as_str), Some("sk-1"));
+        assert_eq!(
+            schema::get(&secrets, "profiles.cloud.api_key").and_then(Value::as_str),
+            Some("sk-2")
+        );
+        assert!(schema::get(&table, "api_key").is_none());
+        assert!(schema::get(&table, "profiles.cloud").is_none());
+        assert!(schema::get(&table, "profiles.gpu.model").is_some());
+    }
+
+    #[test]
+    fn encrypts_with_passphrase() {
+        let encrypted = encrypt("sk-secret", "hunter2").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("sk-secret"));
+
+        assert_eq!(decrypt(&encrypted, "hunter2").unwrap(), "sk-secret");
+        assert!(decrypt(&encrypted, "wrong").is_err());
+    }
+}
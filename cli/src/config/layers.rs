@@ -0,0 +1,162 @@
+//! Where config values come from, lowest precedence first: built-in
+//! defaults, the user config and credentials, a project `.animaforge.toml`, the selected
+//! profile, `ANIMAFORGE_*` environment variables and command line flags.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Project config file, looked up from the current directory upwards
+pub const PROJECT_FILE: &str = ".animaforge.toml";
+
+/// Environment variables that are not config values
+const RESERVED_VARS: &[&str] = &["ANIMAFORGE_PROFILE"];
+
+/// Where a value came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    Default,
+    User(PathBuf),
+    Credentials(PathBuf),
+    Project(PathBuf),
+    Profile(String),
+    Env(String),
+    Flag(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::User(path) => write!(f, "user:{}", path.display()),
+            Origin::Credentials(path) => write!(f, "credentials:{}", path.display()),
+            Origin::Project(path) => write!(f, "project:{}", path.display()),
+            Origin::Profile(name) => write!(f, "profile:{}", name),
+            Origin::Env(var) => write!(f, "env:{}", var),
+            Origin::Flag(flag) => write!(f, "flag:{}", flag),
+        }
+    }
+}
+
+/// What a config was resolved from, so changes can be written back to the
+/// user config alone
+#[derive(Debug, Clone, Default)]
+pub struct Layers {
+    /// The user config and credentials files as written
+    pub user: Table,
+    pub project: Option<PathBuf>,
+    /// Effective values right after loading
+    pub loaded: Table,
+    /// Origin of every value by dotted key
+    pub origins: BTreeMap<String, Origin>,
+}
+
+/// Closest `.animaforge.toml` in `start` or one of its parents
+pub fn find_project_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+pub fn read_file(path: &Path) -> Result<Table> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    toml::from_str(&content)
+        .with_context(|| format!("Failed to parse config file {}", path.display()))
+}
+
+/// Merge `layer` into `table` key by key, recording where each value came from
+pub fn merge(
+    table: &mut Table,
+    layer: &Table,
+    prefix: &str,
+    origin: &Origin,
+    origins: &mut BTreeMap<String, Origin>,
+) {
+    for (name, value) in layer {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+
+        match (table.get_mut(name), value) {
+            (Some(Value::Table(existing)), Value::Table(layer)) => {
+                merge(existing, layer, &key, origin, origins);
+            }
+            _ => {
+                table.insert(name.clone(), value.clone());
+                let mut values = Table::new();
+                values.insert(name.clone(), value.clone());
+                for (key, _) in super::schema::leaves(&values, prefix) {
+                    origins.insert(key, origin.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Config key of an `ANIMAFORGE_*` variable; double underscores separate
+/// nested keys, so `ANIMAFORGE_THUMBNAILS__WIDTH` is `thumbnails.width`
+pub fn env_key(var: &str) -> Option<String> {
+    if RESERVED_VARS.contains(&var) {
+        return None;
+    }
+    let name = var.strip_prefix("ANIMAFORGE_")?;
+    if name.is_empty() {
+        return None;
+    }
+    Some(name.to_ascii_lowercase().replace("__", "."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_env_vars_to_keys() {
+        assert_eq!(env_key("ANIMAFORGE_MODEL").as_deref(), Some("model"));
+        assert_eq!(
+            env_key("ANIMAFORGE_OLLAMA_ENDPOINT").as_deref(),
+            Some("ollama_endpoint")
+        );
+        assert_eq!(
+            env_key("ANIMAFORGE_THUMBNAILS__WIDTH").as_deref(),
+            Some("thumbnails.width")
+        );
+        assert_eq!(env_key("ANIMAFORGE_PROFILE"), None);
+        assert_eq!(env_key("HOME"), None);
+    }
+
+    #[test]
+    fn later_layers_win_key_by_key() {
+        let mut origins = BTreeMap::new();
+        let mut table: Table = toml::from_str("model = \"a\"\n[thumbnails]\nwidth = 480\ngif = false").unwrap();
+        let project: Table = toml::from_str("[thumbnails]\nwidth = 960").unwrap();
+        let origin = Origin::Project(PathBuf::from("/p/.animaforge.toml"));
+
+        merge(&mut table, &project, "", &origin, &mut origins);
+
+        assert_eq!(table["thumbnails"]["width"].as_integer(), Some(960));
+        assert_eq!(table["thumbnails"]["gif"].as_bool(), Some(false));
+        assert_eq!(table["model"].as_str(), Some("a"));
+        assert_eq!(origins.get("thumbnails.width"), Some(&origin));
+        assert_eq!(origins.get("thumbnails.gif"), None);
+    }
+
+    #[test]
+    fn finds_project_file_in_parents() {
+        let root = std::env::temp_dir().join(format!("animaforge-layers-{}", std::process::id()));
+        let nested = root.join("scenes/intro");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(PROJECT_FILE), "quality = \"high\"").unwrap();
+
+        assert_eq!(find_project_file(&nested), Some(root.join(PROJECT_FILE)));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
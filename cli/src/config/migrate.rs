@@ -0,0 +1,102 @@
+//! Upgrades of older config files. Each migration brings a file from the
+//! version before it to its own; files without `config_version` are version 0.
+
+use anyhow::Result;
+use toml::{Table, Value};
+
+/// Version written by this release
+pub const CURRENT_VERSION: i64 = 1;
+
+struct Migration {
+    version: i64,
+    description: &'static str,
+    apply: fn(&mut Table),
+}
+
+/// In order of `version`. Add new ones at the end and bump CURRENT_VERSION.
+/// Version 1 only added `config_version`, so there is nothing to upgrade yet.
+const MIGRATIONS: &[Migration] = &[];
+
+/// Version of a config table
+pub fn version(table: &Table) -> Result<i64> {
+    match table.get("config_version") {
+        None => Ok(0),
+        Some(Value::Integer(version)) if *version >= 0 => Ok(*version),
+        Some(value) => anyhow::bail!("config_version must be a version number, got {}", value),
+    }
+}
+
+/// Upgrade `table` to the current version. Returns what was changed.
+pub fn migrate(table: &mut Table) -> Result<Vec<&'static str>> {
+    migrate_with(table, MIGRATIONS)
+}
+
+fn migrate_with(table: &mut Table, migrations: &[Migration]) -> Result<Vec<&'static str>> {
+    let from = version(table)?;
+    if from > CURRENT_VERSION {
+        anyhow::bail!(
+            "The config is version {}, but this animaforge only knows up to version {}. \
+             Upgrade animaforge",
+            from,
+            CURRENT_VERSION
+        );
+    }
+
+    let mut applied = Vec::new();
+    for migration in migrations.iter().filter(|m| m.version > from) {
+        let before = table.clone();
+        (migration.apply)(table);
+        if *table != before {
+            applied.push(migration.description);
+        }
+    }
+    table.insert("config_version".to_string(), Value::Integer(CURRENT_VERSION));
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename_name(table: &mut Table) {
+        if let Some(value) = table.remove("name") {
+            table.insert("title".to_string(), value);
+        }
+    }
+
+    #[test]
+    fn applies_pending_migrations() {
+        let migrations = [Migration {
+            version: 1,
+            description: "Renamed name to title",
+            apply: rename_name,
+        }];
+        let mut table: Table = toml::from_str("name = \"demo\"").unwrap();
+
+        let applied = migrate_with(&mut table, &migrations).unwrap();
+
+        assert_eq!(applied, vec!["Renamed name to title"]);
+        assert_eq!(table["title"].as_str(), Some("demo"));
+        assert_eq!(version(&table).unwrap(), CURRENT_VERSION);
+
+        // Already current: nothing to do
+        assert!(migrate_with(&mut table, &migrations).unwrap().is_empty());
+    }
+
+    #[test]
+    fn stamps_files_without_a_version() {
+        let mut table: Table = toml::from_str("model = \"llama3\"").unwrap();
+        assert!(migrate(&mut table).unwrap().is_empty());
+        assert_eq!(version(&table).unwrap(), CURRENT_VERSION);
+        assert_eq!(table["model"].as_str(), Some("llama3"));
+    }
+
+    #[test]
+    fn refuses_newer_versions() {
+        let mut table: Table = toml::from_str("config_version = 99").unwrap();
+        assert!(migrate(&mut table).is_err());
+
+        let mut table: Table = toml::from_str("config_version = \"one\"").unwrap();
+        assert!(migrate(&mut table).is_err());
+    }
+}
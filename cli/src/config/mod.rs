@@ -1,29 +1,154 @@
+pub mod credentials;
+pub mod layers;
+pub mod migrate;
 pub mod providers;
+pub mod schema;
 
 use anyhow::{Context, Result};
+use colored::Colorize;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml::{Table, Value};
+
+use crate::utils::output::{self, ErrorCode};
+use layers::{Layers, Origin};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Format version of the file, see `migrate`
+    #[serde(default)]
+    pub config_version: i64,
     pub backend: String,
     pub model: String,
     pub api_key: Option<String>,
     pub output_dir: Option<String>,
+    /// Default render quality: low, medium or high
+    pub quality: Option<String>,
     pub ollama_endpoint: Option<String>,
+    /// Marketplace API, e.g. http://localhost:8080
+    pub marketplace_url: Option<String>,
     pub marketplace_token: Option<String>,
+    /// When to render inside a sandbox: "always", "untrusted" (files not
+    /// produced by `create` on this machine) or "never"
+    pub sandbox: Option<String>,
+    /// Profile used when neither `--profile` nor ANIMAFORGE_PROFILE picks one
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub thumbnails: ThumbnailConfig,
+    /// Named render settings selected with `render --target`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targets: BTreeMap<String, RenderTarget>,
+    /// Named overrides of any of the fields above, e.g. [profiles.gpu]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Table>,
+    /// Profile applied by `load`
+    #[serde(skip)]
+    pub active_profile: Option<ActiveProfile>,
+    /// Files and variables the values came from; `None` for a config that
+    /// was not loaded
+    #[serde(skip)]
+    pub layers: Option<Layers>,
+}
+
+/// A dotted key that no config field reads
+#[derive(Debug, thiserror::Error)]
+#[error("Unknown config key: {0}")]
+pub struct UnknownKey(pub String);
+
+/// Where the marketplace API listens by default
+pub const DEFAULT_MARKETPLACE_URL: &str = "http://localhost:8080";
+
+/// Where Ollama listens by default
+pub const DEFAULT_OLLAMA_ENDPOINT: &str = "http://localhost:11434";
+
+/// Name of the profile meaning "no profile", e.g. `--profile default`
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Set from the global `--profile` flag
+static PROFILE_FLAG: OnceLock<String> = OnceLock::new();
+
+/// Use this profile instead of ANIMAFORGE_PROFILE or the config's `profile`
+pub fn select_profile(name: String) {
+    PROFILE_FLAG.set(name).ok();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveProfile {
+    pub name: String,
+    /// What chose it: "--profile", "ANIMAFORGE_PROFILE" or "config"
+    pub source: &'static str,
+}
+
+/// Output format and frame of one publishing target, e.g. 9:16 shorts.
+/// Anything left out keeps the quality preset's value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RenderTarget {
+    /// Pixel size as WIDTHxHEIGHT, e.g. "1080x1920"
+    pub resolution: Option<String>,
+    pub fps: Option<u32>,
+    /// Container manim writes: mp4, mov, webm or gif
+    pub format: Option<String>,
+    /// Background color, e.g. "#1e1e2e" or "WHITE"
+    pub background: Option<String>,
+    /// Scene units across the frame; defaults to the height times the aspect ratio
+    pub frame_width: Option<f64>,
+    /// Scene units from bottom to top (manim's default is 8)
+    pub frame_height: Option<f64>,
+}
+
+/// Poster frame and preview GIF generated after each successful render
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThumbnailConfig {
+    pub enabled: bool,
+    /// Image format: png or webp
+    pub format: String,
+    /// Timestamp of the poster frame in seconds
+    pub at: f64,
+    /// Width in pixels; the height keeps the aspect ratio
+    pub width: u32,
+    /// Also make a short looping GIF starting at `at`
+    pub gif: bool,
+    /// Length of the GIF in seconds
+    pub gif_duration: f64,
+}
+
+impl Default for ThumbnailConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            format: "png".to_string(),
+            at: 1.0,
+            width: 480,
+            gif: false,
+            gif_duration: 3.0,
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            config_version: migrate::CURRENT_VERSION,
             backend: "ollama".to_string(),
             model: "llama2".to_string(),
             api_key: None,
             output_dir: Some("./animations".to_string()),
-            ollama_endpoint: Some("http://localhost:11434".to_string()),
+            quality: None,
+            ollama_endpoint: Some(DEFAULT_OLLAMA_ENDPOINT.to_string()),
+            marketplace_url: Some(DEFAULT_MARKETPLACE_URL.to_string()),
             marketplace_token: None,
+            sandbox: None,
+            profile: None,
+            thumbnails: ThumbnailConfig::default(),
+            targets: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            active_profile: None,
+            layers: None,
         }
     }
 }
@@ -40,25 +165,576 @@ impl Config {
         Ok(Self::config_dir()?.join("config.toml"))
     }
 
+    /// Where secrets are kept, see `credentials`
+    pub fn credentials_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join(credentials::FILE))
+    }
+
+    /// Whether a config file has been written, e.g. by `animaforge init`
+    pub fn exists() -> bool {
+        Self::config_path().is_ok_and(|path| path.exists())
+    }
+
+    /// Resolve the config from all layers: defaults, the user config, the
+    /// project's `.animaforge.toml`, the selected profile and `ANIMAFORGE_*`
+    /// environment variables
     pub fn load() -> Result<Self> {
-        let config_path = Self::config_path()?;
+        Self::resolve(true, None).map_err(|e| output::with_code(ErrorCode::Config, e))
+    }
+
+    /// Like `load`, but without applying a profile
+    pub fn load_base() -> Result<Self> {
+        Self::resolve(false, None).map_err(|e| output::with_code(ErrorCode::Config, e))
+    }
+
+    /// `user` replaces the user config file's content when given
+    fn resolve(with_profile: bool, user: Option<Table>) -> Result<Self> {
+        let mut origins = BTreeMap::new();
+        let mut table = Table::new();
+        layers::merge(
+            &mut table,
+            &Self::default().to_table()?,
+            "",
+            &Origin::Default,
+            &mut origins,
+        );
+
+        let user_path = Self::config_path()?;
+        let credentials_path = Self::credentials_path()?;
+        let user = match user {
+            Some(user) => user,
+            None => Self::read_user_files(&user_path, &credentials_path)?,
+        };
+        let mut plain = user.clone();
+        let secrets = credentials::split(&mut plain);
+        layers::merge(&mut table, &plain, "", &Origin::User(user_path.clone()), &mut origins);
+        layers::merge(&mut table, &secrets, "", &Origin::Credentials(credentials_path), &mut origins);
+
+        let project = std::env::current_dir()
+            .ok()
+            .and_then(|dir| layers::find_project_file(&dir));
+        let mut project_content = None;
+        if let Some(path) = &project {
+            let mut content = layers::read_file(path)?;
+            // Project files are shared, so they are only upgraded in memory
+            migrate::migrate(&mut content)
+                .with_context(|| format!("Can't read {}", path.display()))?;
+            // Project files tend to be committed
+            for (key, value) in schema::leaves(&content, "") {
+                let plaintext = value.as_str().is_some_and(|v| credentials::describe(v).is_none());
+                if credentials::is_secret(&key) && plaintext {
+                    anyhow::bail!(
+                        "{} must not contain the secret {}. Use `{} = \"env:VAR\"` or `animaforge config set {}`",
+                        path.display(),
+                        key,
+                        key,
+                        key
+                    );
+                }
+            }
+            layers::merge(&mut table, &content, "", &Origin::Project(path.clone()), &mut origins);
+            project_content = Some(content);
+        }
+
+        let files: Vec<(&Table, &Path)> = std::iter::once((&plain, user_path.as_path()))
+            .chain(project_content.as_ref().zip(project.as_deref()))
+            .collect();
+        let mut config = Self::from_table(table).map_err(|e| {
+            files
+                .iter()
+                .find_map(|(layer, path)| Self::parse_error(layer, path))
+                .unwrap_or_else(|| anyhow::anyhow!("Failed to parse config file: {}", e.message()))
+        })?;
+        for (layer, path) in &files {
+            for key in Self::unknown_keys(layer) {
+                eprintln!(
+                    "{} Ignoring unknown config key {} in {}",
+                    "⚠".yellow(),
+                    Self::describe_unknown(&key),
+                    path.display()
+                );
+            }
+        }
+        config.layers = Some(Layers {
+            user,
+            project,
+            loaded: Table::new(),
+            origins,
+        });
+
+        if with_profile {
+            let selected = PROFILE_FLAG
+                .get()
+                .map(|name| (name.clone(), "--profile"))
+                .or_else(|| {
+                    std::env::var("ANIMAFORGE_PROFILE")
+                        .ok()
+                        .filter(|name| !name.is_empty())
+                        .map(|name| (name, "ANIMAFORGE_PROFILE"))
+                })
+                .or_else(|| config.profile.clone().map(|name| (name, "config")));
+
+            if let Some((name, source)) = selected {
+                if name != DEFAULT_PROFILE {
+                    config.apply_profile(&name, source)?;
+                }
+            }
+        }
+
+        config.apply_env()?;
+
+        let loaded = config.to_table()?;
+        if let Some(layers) = &mut config.layers {
+            layers.loaded = loaded;
+        }
+        Ok(config)
+    }
+
+    /// The user config and credentials as one table. Render targets and
+    /// plaintext secrets still in the config file are moved where they belong.
+    fn read_user_files(config_path: &Path, credentials_path: &Path) -> Result<Table> {
+        let mut user = if config_path.exists() {
+            layers::read_file(config_path)?
+        } else {
+            Table::new()
+        };
+        let mut changed = false;
+
+        let from = migrate::version(&user)?;
+        let applied = migrate::migrate(&mut user)
+            .with_context(|| format!("Can't read {}", config_path.display()))?;
+        if config_path.exists() && !applied.is_empty() {
+            let backup = config_path.with_file_name(format!("config.v{}.bak", from));
+            fs::copy(config_path, &backup).context("Failed to back up config file")?;
+            // The old file may hold secrets that are about to move
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&backup, fs::Permissions::from_mode(0o600))?;
+            }
+            eprintln!(
+                "Upgraded {} to config version {} (backup: {})",
+                config_path.display(),
+                migrate::CURRENT_VERSION,
+                backup.display()
+            );
+            for description in applied {
+                eprintln!("  {}", description);
+            }
+            changed = true;
+        }
+
+        let mut secrets = if credentials_path.exists() {
+            credentials::read(credentials_path)?
+        } else {
+            Table::new()
+        };
+        let plaintext = credentials::split(&mut user);
+        if !plaintext.is_empty() {
+            let keys: Vec<String> = schema::leaves(&plaintext, "")
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect();
+            layers::merge(&mut secrets, &plaintext, "", &Origin::Default, &mut BTreeMap::new());
+            // Keep the secrets safe before removing them from the config
+            credentials::write(credentials_path, &secrets)?;
+            eprintln!(
+                "Moved {} from {} to {}",
+                keys.join(", "),
+                config_path.display(),
+                credentials_path.display()
+            );
+            changed = true;
+        }
+
+        if changed {
+            fs::write(config_path, toml::to_string_pretty(&user)?)
+                .context("Failed to write config file")?;
+        }
+
+        layers::merge(&mut user, &secrets, "", &Origin::Default, &mut BTreeMap::new());
+        Ok(user)
+    }
+
+    /// Apply `ANIMAFORGE_*` variables naming config keys. Other variables
+    /// with the prefix, like ANIMAFORGE_ENGINE, are left alone.
+    fn apply_env(&mut self) -> Result<()> {
+        let mut vars: Vec<(String, String, String)> = std::env::vars()
+            .filter_map(|(var, value)| Some((layers::env_key(&var)?, var, value)))
+            .collect();
+        vars.sort();
+
+        // Older name of ANIMAFORGE_MARKETPLACE_URL
+        if let Ok(url) = std::env::var("MARKETPLACE_API_URL") {
+            if !vars.iter().any(|(key, _, _)| key == "marketplace_url") {
+                vars.push(("marketplace_url".to_string(), "MARKETPLACE_API_URL".to_string(), url));
+            }
+        }
+
+        for (key, var, value) in vars {
+            match self.assign(&key, &value) {
+                Ok(()) => self.record_origin(&key, Origin::Env(var)),
+                Err(e) if e.is::<UnknownKey>() => {}
+                Err(e) => return Err(e).with_context(|| format!("Invalid {}", var)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Override a value from a command line flag like `--quality`
+    pub fn set_flag(&mut self, key: &str, text: &str, flag: &str) -> Result<()> {
+        self.assign(key, text)
+            .with_context(|| format!("Invalid {}", flag))?;
+        self.record_origin(key, Origin::Flag(flag.to_string()));
+        Ok(())
+    }
+
+    fn record_origin(&mut self, key: &str, origin: Origin) {
+        if let Some(layers) = &mut self.layers {
+            layers.origins.insert(key.to_string(), origin);
+        }
+    }
+
+    /// Where each value came from, by dotted key
+    pub fn origins(&self) -> BTreeMap<String, Origin> {
+        self.layers
+            .as_ref()
+            .map(|layers| layers.origins.clone())
+            .unwrap_or_default()
+    }
+
+    /// Apply a profile's overrides on top of this config
+    pub fn apply_profile(&mut self, name: &str, source: &'static str) -> Result<()> {
+        let overrides = match self.profiles.get(name) {
+            Some(overrides) => overrides.clone(),
+            None if self.profiles.is_empty() => anyhow::bail!(
+                "Unknown profile '{}'. Create it with `animaforge config profile create {}`",
+                name,
+                name
+            ),
+            None => anyhow::bail!(
+                "Unknown profile '{}'. Available: {}",
+                name,
+                self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        };
+
+        let mut table = self.to_table()?;
+        let keys = schema::leaves(&overrides, "");
+        for (key, value) in &keys {
+            if key == "profile" || key.starts_with("profiles.") {
+                anyhow::bail!("Profile '{}' can't set {}", name, key);
+            }
+            schema::insert(&mut table, key, (*value).clone())?;
+        }
+
+        let mut config = self
+            .rebuild(table)
+            .map_err(|e| anyhow::anyhow!("Invalid profile '{}': {}", name, e.message()))?;
+        for (key, _) in &keys {
+            if config.get(key)?.is_none() {
+                anyhow::bail!("Unknown config key '{}' in profile '{}'", key, name);
+            }
+            config.record_origin(key, Origin::Profile(name.to_string()));
+        }
+
+        config.active_profile = Some(ActiveProfile {
+            name: name.to_string(),
+            source,
+        });
+        *self = config;
+        Ok(())
+    }
+
+    /// Render quality used when a command is not given one
+    pub fn quality(&self) -> &str {
+        self.quality.as_deref().unwrap_or("medium")
+    }
+
+    /// API key for cloud backends, with `env:` and encrypted values resolved
+    pub fn api_key(&self) -> Result<Option<String>> {
+        self.api_key
+            .as_deref()
+            .map(|value| credentials::reveal("api_key", value))
+            .transpose()
+    }
+
+    pub fn marketplace_token(&self) -> Result<Option<String>> {
+        self.marketplace_token
+            .as_deref()
+            .map(|value| credentials::reveal("marketplace_token", value))
+            .transpose()
+    }
 
-        if !config_path.exists() {
-            // Create default config
-            let config = Self::default();
-            config.save()?;
-            return Ok(config);
+    pub fn ollama_endpoint(&self) -> &str {
+        self.ollama_endpoint
+            .as_deref()
+            .unwrap_or(DEFAULT_OLLAMA_ENDPOINT)
+            .trim_end_matches('/')
+    }
+
+    pub fn marketplace_url(&self) -> &str {
+        self.marketplace_url
+            .as_deref()
+            .unwrap_or(DEFAULT_MARKETPLACE_URL)
+            .trim_end_matches('/')
+    }
+
+    /// Render target by name
+    pub fn target(&self, name: &str) -> Result<RenderTarget> {
+        match self.targets.get(name) {
+            Some(target) => Ok(target.clone()),
+            None if self.targets.is_empty() => anyhow::bail!(
+                "Unknown render target '{}'. Define it as [targets.{}] in {}",
+                name,
+                name,
+                Self::config_path()?.display()
+            ),
+            None => anyhow::bail!(
+                "Unknown render target '{}'. Available: {}",
+                name,
+                self.targets.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
         }
+    }
 
-        let content = fs::read_to_string(&config_path)
-            .context("Failed to read config file")?;
+    /// Parse and validate config file content. Fields it leaves out keep
+    /// their defaults.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut layer: Table = toml::from_str(content).context("Failed to parse config file")?;
+        migrate::migrate(&mut layer)?;
 
-        let config: Config = toml::from_str(&content)
-            .context("Failed to parse config file")?;
+        let unknown = Self::unknown_keys(&layer);
+        if !unknown.is_empty() {
+            let keys: Vec<String> = unknown.iter().map(|key| Self::describe_unknown(key)).collect();
+            anyhow::bail!("Unknown config keys: {}", keys.join(", "));
+        }
 
+        let mut table = Self::default().to_table()?;
+        layers::merge(&mut table, &layer, "", &Origin::Default, &mut BTreeMap::new());
+
+        let config = Self::from_table(table).map_err(|e| {
+            Self::parse_error(&layer, Path::new("config file"))
+                .unwrap_or_else(|| anyhow::anyhow!("Failed to parse config file: {}", e.message()))
+        })?;
+        config.validate()?;
         Ok(config)
     }
 
+    /// Keys of a config layer that no field reads
+    fn unknown_keys(layer: &Table) -> Vec<String> {
+        let mut unknown = Vec::new();
+        let mut check = |prefix: String, values: &Table| {
+            let Some(read) = Self::read_back(values) else {
+                return;
+            };
+            for (key, _) in schema::leaves(values, "") {
+                if !key.starts_with("profiles.") && schema::get(&read, &key).is_none() {
+                    unknown.push(format!("{}{}", prefix, key));
+                }
+            }
+        };
+
+        check(String::new(), layer);
+        if let Some(Value::Table(profiles)) = layer.get("profiles") {
+            for (name, overrides) in profiles {
+                if let Value::Table(overrides) = overrides {
+                    check(format!("profiles.{}.", name), overrides);
+                }
+            }
+        }
+        unknown
+    }
+
+    /// `values` on top of the defaults, as the fields read them
+    fn read_back(values: &Table) -> Option<Table> {
+        let mut table = Self::default().to_table().ok()?;
+        layers::merge(&mut table, values, "", &Origin::Default, &mut BTreeMap::new());
+        Self::from_table(table).ok()?.to_table().ok()
+    }
+
+    /// Every key `config set` knows, except those inside targets and profiles
+    pub fn known_keys() -> Vec<String> {
+        let mut known: Vec<String> = Self::default()
+            .to_table()
+            .map(|table| schema::leaves(&table, "").into_iter().map(|(k, _)| k).collect())
+            .unwrap_or_default();
+        for key in schema::field_names() {
+            if !known.iter().any(|k| k == key) {
+                known.push(key.to_string());
+            }
+        }
+        known
+    }
+
+    /// "'key'", with the closest known key if there is one
+    fn describe_unknown(key: &str) -> String {
+        let (prefix, name) = match key.strip_prefix("profiles.") {
+            Some(rest) => match rest.split_once('.') {
+                Some((profile, name)) => (format!("profiles.{}.", profile), name),
+                None => (String::new(), key),
+            },
+            None => (String::new(), key),
+        };
+
+        let known = Self::known_keys();
+        match schema::closest(name, known.iter().map(String::as_str)) {
+            Some(suggestion) => format!("'{}' (did you mean '{}{}'?)", key, prefix, suggestion),
+            None => format!("'{}'", key),
+        }
+    }
+
+    /// Error naming the key of `layer` that doesn't fit its field, if one is
+    /// to blame
+    fn parse_error(layer: &Table, path: &Path) -> Option<anyhow::Error> {
+        let defaults = Self::default().to_table().ok()?;
+        schema::leaves(layer, "").into_iter().find_map(|(key, value)| {
+            let mut single = Table::new();
+            schema::insert(&mut single, &key, value.clone()).ok()?;
+            let mut table = defaults.clone();
+            layers::merge(&mut table, &single, "", &Origin::Default, &mut BTreeMap::new());
+
+            let error = Self::from_table(table).err()?;
+            let message = error.message().trim().to_string();
+            // A lone value of a table with required fields, e.g. a render target
+            let key = if message.starts_with("missing field") {
+                key.rsplit_once('.').map(|(parent, _)| parent.to_string()).unwrap_or(key)
+            } else {
+                key
+            };
+            Some(anyhow::anyhow!(
+                "Invalid {} in {}: {}",
+                key,
+                path.display(),
+                message
+            ))
+        })
+    }
+
+    /// Check values beyond their types: URLs, paths, enums and targets
+    pub fn validate(&self) -> Result<()> {
+        schema::check_all(&self.to_table()?)?;
+        for (name, overrides) in &self.profiles {
+            schema::check_all(overrides).with_context(|| format!("Invalid profile '{}'", name))?;
+        }
+        for (name, target) in &self.targets {
+            crate::manim::target::validate(name, target)?;
+        }
+        Ok(())
+    }
+
+    fn to_table(&self) -> Result<Table> {
+        Table::try_from(self).context("Failed to serialize config")
+    }
+
+    fn from_table(table: Table) -> Result<Self, toml::de::Error> {
+        Value::Table(table).try_into()
+    }
+
+    /// `from_table`, keeping what is not serialized
+    fn rebuild(&self, table: Table) -> Result<Self, toml::de::Error> {
+        let mut config = Self::from_table(table)?;
+        config.active_profile = self.active_profile.clone();
+        config.layers = self.layers.clone();
+        Ok(config)
+    }
+
+    /// Value of a dotted key like `thumbnails.width`; `None` if it is not set
+    pub fn get(&self, key: &str) -> Result<Option<Value>> {
+        Ok(schema::get(&self.to_table()?, key).cloned())
+    }
+
+    /// Set a dotted key from command line text. The field's type decides how
+    /// the text is read, so every field is settable without extra code.
+    pub fn set(&mut self, key: &str, text: &str) -> Result<()> {
+        let mut updated = self.clone();
+        updated.assign(key, text)?;
+        updated.validate()?;
+        *self = updated;
+        Ok(())
+    }
+
+    /// `set` checking only this key, for layers applied while loading
+    fn assign(&mut self, key: &str, text: &str) -> Result<()> {
+        let mut error = None;
+
+        for value in schema::candidates(text) {
+            let mut table = self.to_table()?;
+            // Only fails when a parent of the key is a plain value
+            schema::insert(&mut table, key, value.clone())
+                .map_err(|_| UnknownKey(key.to_string()))?;
+
+            match self.rebuild(table) {
+                Ok(updated) => {
+                    // Unknown keys are dropped when deserializing
+                    if updated.get(key)?.is_none() {
+                        return Err(UnknownKey(key.to_string()).into());
+                    }
+                    schema::check(key, &value)?;
+                    *self = updated;
+                    return Ok(());
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+
+        let error = error.map(|e| e.message().to_string()).unwrap_or_default();
+        anyhow::bail!("Invalid value for {}: {}", key, error)
+    }
+
+    /// Remove a dotted key, falling back to the value of the layers below.
+    /// For a loaded config that is the user file, or the active profile.
+    /// Returns false if it was not set there.
+    pub fn unset(&mut self, key: &str) -> Result<bool> {
+        if let Some(layers) = &self.layers {
+            let mut user = layers.user.clone();
+            let removed = match &self.active_profile {
+                Some(active) => schema::get(&user, "profiles")
+                    .and_then(|profiles| profiles.get(&active.name))
+                    .is_some()
+                    .then(|| {
+                        let profiles = user.get_mut("profiles")?.as_table_mut()?;
+                        let overrides = profiles.get_mut(&active.name)?.as_table_mut()?;
+                        schema::remove(overrides, key)
+                    })
+                    .flatten(),
+                None => schema::remove(&mut user, key),
+            };
+            if removed.is_none() {
+                return Ok(false);
+            }
+
+            *self = Self::resolve(self.active_profile.is_some(), Some(user))
+                .map_err(|e| Self::unset_error(key, e))?;
+            return Ok(true);
+        }
+
+        let mut table = self.to_table()?;
+        if schema::remove(&mut table, key).is_none() {
+            return Ok(false);
+        }
+
+        *self = Self::from_table(table).map_err(|e| {
+            if e.message().starts_with("missing field") {
+                anyhow::anyhow!("{} is required and can't be unset", key)
+            } else {
+                anyhow::anyhow!("Can't unset {}: {}", key, e.message())
+            }
+        })?;
+        Ok(true)
+    }
+
+    fn unset_error(key: &str, error: anyhow::Error) -> anyhow::Error {
+        if format!("{:#}", error).contains("missing field") {
+            anyhow::anyhow!("{} is required and can't be unset", key)
+        } else {
+            error.context(format!("Can't unset {}", key))
+        }
+    }
+
+    /// Write the user config. Only values changed since loading are written,
+    /// into the active profile if there is one, so defaults, the project file
+    /// and environment variables don't end up in it.
     pub fn save(&self) -> Result<()> {
         let config_dir = Self::config_dir()?;
 
@@ -67,13 +743,171 @@ impl Config {
             .context("Failed to create config directory")?;
 
         let config_path = Self::config_path()?;
+        let credentials_path = Self::credentials_path()?;
 
-        let content = toml::to_string_pretty(self)
-            .context("Failed to serialize config")?;
+        let mut user = match &self.layers {
+            None => self.to_table()?,
+            Some(layers) => self.user_table(layers)?,
+        };
+        user.insert(
+            "config_version".to_string(),
+            Value::Integer(migrate::CURRENT_VERSION),
+        );
+        let mut secrets = credentials::split(&mut user);
 
+        if !secrets.is_empty() || credentials_path.exists() {
+            // Once encrypted, new secrets are encrypted too
+            let stored = if credentials_path.exists() {
+                credentials::read(&credentials_path)?
+            } else {
+                Table::new()
+            };
+            let encrypted = schema::leaves(&stored, "")
+                .into_iter()
+                .find_map(|(_, value)| value.as_str().filter(|v| credentials::is_encrypted(v)));
+            if let Some(encrypted) = encrypted {
+                let passphrase = credentials::passphrase(false)?;
+                credentials::decrypt(encrypted, &passphrase)?;
+                credentials::encrypt_all(&mut secrets, &passphrase)?;
+            }
+            credentials::write(&credentials_path, &secrets)?;
+        }
+
+        let content = toml::to_string_pretty(&user).context("Failed to serialize config")?;
         fs::write(&config_path, content)
             .context("Failed to write config file")?;
 
         Ok(())
     }
+
+    /// The user file with the changes since loading
+    fn user_table(&self, layers: &Layers) -> Result<Table> {
+        let mut user = layers.user.clone();
+        let current = self.to_table()?;
+        let loaded = &layers.loaded;
+
+        // Profiles are defined in the user file, whichever one is active
+        if schema::get(&current, "profile") != schema::get(loaded, "profile") {
+            match &self.profile {
+                Some(name) => schema::insert(&mut user, "profile", Value::String(name.clone()))?,
+                None => {
+                    schema::remove(&mut user, "profile");
+                }
+            }
+        }
+        let loaded_profiles = loaded.get("profiles").and_then(Value::as_table);
+        let names: std::collections::BTreeSet<&String> = self
+            .profiles
+            .keys()
+            .chain(loaded_profiles.into_iter().flat_map(|p| p.keys()))
+            .collect();
+        for name in names {
+            let before = loaded_profiles.and_then(|p| p.get(name)).and_then(Value::as_table);
+            let after = self.profiles.get(name);
+            if before != after {
+                let key = format!("profiles.{}", name);
+                match after {
+                    Some(overrides) => schema::insert(&mut user, &key, Value::Table(overrides.clone()))?,
+                    None => {
+                        schema::remove(&mut user, &key);
+                    }
+                }
+            }
+        }
+
+        let mut target = Table::new();
+        let is_value = |key: &str| key != "profile" && !key.starts_with("profiles.");
+        for (key, value) in schema::leaves(&current, "") {
+            if is_value(&key) && schema::get(loaded, &key) != Some(value) {
+                schema::insert(&mut target, &key, value.clone())?;
+            }
+        }
+        let removed: Vec<String> = schema::leaves(loaded, "")
+            .into_iter()
+            .map(|(key, _)| key)
+            .filter(|key| is_value(key) && schema::get(&current, key).is_none())
+            .collect();
+
+        let destination = match &self.active_profile {
+            Some(active) => {
+                let key = format!("profiles.{}", active.name);
+                if schema::get(&user, &key).is_none() {
+                    schema::insert(&mut user, &key, Value::Table(Table::new()))?;
+                }
+                user.get_mut("profiles")
+                    .and_then(Value::as_table_mut)
+                    .and_then(|profiles| profiles.get_mut(&active.name))
+                    .and_then(Value::as_table_mut)
+                    .context("Profile is not a table")?
+            }
+            None => &mut user,
+        };
+        for key in removed {
+            schema::remove(destination, &key);
+        }
+        let mut origins = BTreeMap::new();
+        layers::merge(destination, &target, "", &Origin::Default, &mut origins);
+
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sets_and_unsets_fields_by_key() {
+        let mut config = Config::default();
+
+        config.set("thumbnails.width", "640").unwrap();
+        assert_eq!(config.thumbnails.width, 640);
+        config.set("model", "1234").unwrap();
+        assert_eq!(config.model, "1234");
+        config.set("targets.shorts.resolution", "1080x1920").unwrap();
+        assert_eq!(config.target("shorts").unwrap().resolution.as_deref(), Some("1080x1920"));
+
+        assert!(config.set("thumbnails.width", "wide").is_err());
+        assert!(config.set("ollama_endpoint", "localhost").is_err());
+        assert!(config.set("targets.shorts.resolution", "big").is_err());
+        let unknown = config.set("bakend", "ollama").unwrap_err();
+        assert_eq!(unknown.to_string(), "Unknown config key: bakend");
+        assert!(unknown.is::<UnknownKey>());
+        assert!(config.set("model.name", "x").unwrap_err().is::<UnknownKey>());
+
+        assert!(config.unset("thumbnails.width").unwrap());
+        assert_eq!(config.thumbnails.width, ThumbnailConfig::default().width);
+        assert!(!config.unset("api_key").unwrap());
+        assert!(config.unset("backend").is_err());
+    }
+
+    #[test]
+    fn profiles_override_fields() {
+        let mut config: Config = toml::from_str(
+            r#"
+            backend = "ollama"
+            model = "llama3"
+
+            [profiles.gpu]
+            ollama_endpoint = "http://gpu-box:11434"
+            model = "qwen2.5-coder:32b"
+            thumbnails = { width = 960 }
+
+            [profiles.typo]
+            modle = "x"
+            "#,
+        )
+        .unwrap();
+
+        let mut gpu = config.clone();
+        gpu.apply_profile("gpu", "--profile").unwrap();
+        assert_eq!(gpu.model, "qwen2.5-coder:32b");
+        assert_eq!(gpu.ollama_endpoint.as_deref(), Some("http://gpu-box:11434"));
+        assert_eq!(gpu.thumbnails.width, 960);
+        assert_eq!(gpu.thumbnails.format, "png");
+        assert_eq!(gpu.active_profile.unwrap().name, "gpu");
+
+        assert!(config.clone().apply_profile("laptop", "config").is_err());
+        assert!(config.apply_profile("typo", "config").is_err());
+    }
 }
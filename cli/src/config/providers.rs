@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::DEFAULT_OLLAMA_ENDPOINT;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum LLMProvider {
     Ollama {
@@ -20,7 +22,7 @@ impl LLMProvider {
     pub fn from_config(backend: &str, model: &str, api_key: Option<&str>, endpoint: Option<&str>) -> Option<Self> {
         match backend {
             "ollama" => Some(LLMProvider::Ollama {
-                endpoint: endpoint.unwrap_or("http://localhost:11434").to_string(),
+                endpoint: endpoint.unwrap_or(DEFAULT_OLLAMA_ENDPOINT).to_string(),
                 model: model.to_string(),
             }),
             "gemini" => {
@@ -0,0 +1,268 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use toml::{Table, Value};
+
+/// What a config value must look like beyond its serde type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// Anything the field's type accepts
+    Any,
+    /// Masked when shown
+    Secret,
+    /// An http or https URL
+    Url,
+    /// A directory, which may not exist yet
+    Path,
+    /// One of a fixed set of strings
+    Enum(&'static [&'static str]),
+}
+
+/// Fields with more rules than their type. `*` matches any one key segment,
+/// e.g. a target name. Fields left out are still settable as `Kind::Any`.
+const FIELDS: &[(&str, Kind)] = &[
+    ("backend", Kind::Enum(&["ollama", "gemini", "claude"])),
+    ("api_key", Kind::Secret),
+    ("output_dir", Kind::Path),
+    ("quality", Kind::Enum(&["low", "medium", "high"])),
+    ("ollama_endpoint", Kind::Url),
+    ("marketplace_url", Kind::Url),
+    ("marketplace_token", Kind::Secret),
+    ("sandbox", Kind::Enum(&["always", "untrusted", "never"])),
+    ("thumbnails.format", Kind::Enum(&["png", "webp"])),
+    (
+        "targets.*.format",
+        Kind::Enum(&["mp4", "mov", "webm", "gif"]),
+    ),
+];
+
+/// Keys from FIELDS without wildcards
+pub fn field_names() -> impl Iterator<Item = &'static str> {
+    FIELDS
+        .iter()
+        .map(|(key, _)| *key)
+        .filter(|key| !key.contains('*'))
+}
+
+/// The candidate closest to a misspelled key, if any is close
+pub fn closest<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .map(|candidate| (distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance > 0 && *distance <= (key.len() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance between two strings
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+pub fn kind(key: &str) -> Kind {
+    FIELDS
+        .iter()
+        .find(|(pattern, _)| matches(pattern, key))
+        .map(|(_, kind)| *kind)
+        .unwrap_or(Kind::Any)
+}
+
+fn matches(pattern: &str, key: &str) -> bool {
+    let (pattern, key): (Vec<&str>, Vec<&str>) =
+        (pattern.split('.').collect(), key.split('.').collect());
+    pattern.len() == key.len() && pattern.iter().zip(&key).all(|(p, k)| *p == "*" || p == k)
+}
+
+/// Check a value against its field's kind
+pub fn check(key: &str, value: &Value) -> Result<()> {
+    let Some(text) = value.as_str() else {
+        return Ok(());
+    };
+
+    match kind(key) {
+        Kind::Any | Kind::Secret => Ok(()),
+        Kind::Url => {
+            let url = reqwest::Url::parse(text)
+                .with_context(|| format!("{} must be a URL, got '{}'", key, text))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                anyhow::bail!("{} must be an http or https URL, got '{}'", key, text);
+            }
+            Ok(())
+        }
+        Kind::Path => {
+            if text.trim().is_empty() {
+                anyhow::bail!("{} must not be empty", key);
+            }
+            let path = Path::new(text);
+            if path.exists() && !path.is_dir() {
+                anyhow::bail!("{} must be a directory, but {} is a file", key, text);
+            }
+            Ok(())
+        }
+        Kind::Enum(options) => {
+            if !options.contains(&text) {
+                anyhow::bail!(
+                    "Invalid {} '{}'. Valid options: {}",
+                    key,
+                    text,
+                    options.join(", ")
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Check every value of a serialized config
+pub fn check_all(table: &Table) -> Result<()> {
+    for (key, value) in leaves(table, "") {
+        check(&key, value)?;
+    }
+    Ok(())
+}
+
+/// Dotted keys and values of all non-table entries
+pub fn leaves<'a>(table: &'a Table, prefix: &str) -> Vec<(String, &'a Value)> {
+    let mut found = Vec::new();
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            Value::Table(table) => found.extend(leaves(table, &key)),
+            value => found.push((key, value)),
+        }
+    }
+    found
+}
+
+/// Ways to read command line text as a value: as a TOML literal (number,
+/// boolean, array) first, then as a plain string. A quoted string is only
+/// ever a string.
+pub fn candidates(text: &str) -> Vec<Value> {
+    match format!("value = {}", text)
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+    {
+        Some(Value::String(string)) => vec![Value::String(string)],
+        Some(value) => vec![value, Value::String(text.to_string())],
+        None => vec![Value::String(text.to_string())],
+    }
+}
+
+pub fn get<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (parents, name) = split(key);
+    let mut table = table;
+    for parent in parents {
+        table = table.get(parent)?.as_table()?;
+    }
+    table.get(name)
+}
+
+/// Put `value` at a dotted key, creating the tables on the way
+pub fn insert(table: &mut Table, key: &str, value: Value) -> Result<()> {
+    let (parents, name) = split(key);
+    let mut table = table;
+    for parent in parents {
+        table = table
+            .entry(parent.to_string())
+            .or_insert_with(|| Value::Table(Table::new()))
+            .as_table_mut()
+            .with_context(|| format!("Unknown config key: {}", key))?;
+    }
+    table.insert(name.to_string(), value);
+    Ok(())
+}
+
+/// Remove a dotted key; `None` if it was not set
+pub fn remove(table: &mut Table, key: &str) -> Option<Value> {
+    let (parents, name) = split(key);
+    let mut table = table;
+    for parent in parents {
+        table = table.get_mut(parent)?.as_table_mut()?;
+    }
+    table.remove(name)
+}
+
+fn split(key: &str) -> (Vec<&str>, &str) {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let name = parts.pop().unwrap_or_default();
+    (parts, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_kinds() {
+        let text = |s: &str| Value::String(s.to_string());
+
+        assert!(check("ollama_endpoint", &text("http://gpu-box:11434")).is_ok());
+        assert!(check("ollama_endpoint", &text("gpu-box:11434")).is_err());
+        assert!(check("ollama_endpoint", &text("ftp://gpu-box")).is_err());
+        assert!(check("backend", &text("claude")).is_ok());
+        assert!(check("backend", &text("openai")).is_err());
+        assert!(check("targets.shorts.format", &text("avi")).is_err());
+        assert!(check("targets.shorts.background", &text("avi")).is_ok());
+        assert!(check("output_dir", &text("")).is_err());
+        assert!(check("model", &text("anything")).is_ok());
+    }
+
+    #[test]
+    fn reads_values_as_toml_then_text() {
+        assert_eq!(candidates("480"), vec![Value::Integer(480), text("480")]);
+        assert_eq!(
+            candidates("false"),
+            vec![Value::Boolean(false), text("false")]
+        );
+        assert_eq!(candidates("llama3:8b"), vec![text("llama3:8b")]);
+        assert_eq!(candidates("\"480\""), vec![text("480")]);
+
+        fn text(s: &str) -> Value {
+            Value::String(s.to_string())
+        }
+    }
+
+    #[test]
+    fn suggests_close_keys() {
+        let keys = ["model", "backend", "thumbnails.width"];
+        assert_eq!(closest("modle", keys.into_iter()), Some("model"));
+        assert_eq!(closest("thumbnails.widht", keys.into_iter()), Some("thumbnails.width"));
+        assert_eq!(closest("colour", keys.into_iter()), None);
+    }
+
+    #[test]
+    fn edits_nested_keys() {
+        let mut table = Table::new();
+        insert(&mut table, "targets.shorts.fps", Value::Integer(30)).unwrap();
+        assert_eq!(
+            get(&table, "targets.shorts.fps"),
+            Some(&Value::Integer(30))
+        );
+        assert!(insert(&mut table, "targets.shorts.fps.x", Value::Integer(1)).is_err());
+
+        assert_eq!(
+            remove(&mut table, "targets.shorts.fps"),
+            Some(Value::Integer(30))
+        );
+        assert_eq!(remove(&mut table, "targets.shorts.fps"), None);
+        assert_eq!(remove(&mut table, "nothing.here"), None);
+    }
+}
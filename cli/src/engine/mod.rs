@@ -0,0 +1,157 @@
+pub mod protocol;
+#[cfg(unix)]
+pub mod worker;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use protocol::{EngineError, Message, Request, PROTOCOL_VERSION};
+
+/// Module that speaks the JSON protocol on stdin/stdout
+const MODULE: &str = "animaforge_engine.protocol";
+
+/// Python interpreter that runs the engine (`ANIMAFORGE_PYTHON`, default `python3`)
+pub fn python() -> String {
+    std::env::var("ANIMAFORGE_PYTHON").unwrap_or_else(|_| "python3".to_string())
+}
+
+/// Arguments that start the engine in protocol mode
+pub fn args() -> [&'static str; 2] {
+    ["-m", MODULE]
+}
+
+/// Point `command` at the engine sources when `ANIMAFORGE_ENGINE_PATH` is set,
+/// for running from a checkout without installing the package
+pub fn configure(command: &mut Command) {
+    if let Ok(path) = std::env::var("ANIMAFORGE_ENGINE_PATH") {
+        let python_path = match std::env::var("PYTHONPATH") {
+            Ok(existing) if !existing.is_empty() => format!("{}:{}", path, existing),
+            _ => path,
+        };
+        command.env("PYTHONPATH", python_path);
+    }
+}
+
+/// Whether the Python engine is installed. Set `ANIMAFORGE_ENGINE=off` to
+/// always call manim directly.
+pub fn available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+
+    *AVAILABLE.get_or_init(|| {
+        if std::env::var("ANIMAFORGE_ENGINE").is_ok_and(|v| v == "off" || v == "0") {
+            return false;
+        }
+
+        let mut command = Command::new(python());
+        configure(&mut command);
+        command
+            .args(["-c", &format!("import {}", MODULE)])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    })
+}
+
+/// Fail with install instructions when the engine is missing
+pub fn require() -> Result<()> {
+    if !available() {
+        anyhow::bail!(
+            "The AnimaForge Python engine is not installed.\n\
+             Install it with: cd engine && pip install -e .\n\
+             Or point ANIMAFORGE_ENGINE_PATH at the engine directory"
+        );
+    }
+
+    Ok(())
+}
+
+/// Start the engine, send one request and return its result. Progress and
+/// output events are passed to `on_event` as they arrive.
+pub fn request(method: &str, params: Value, mut on_event: impl FnMut(&Message)) -> Result<Value> {
+    require()?;
+
+    let mut command = Command::new(python());
+    configure(&mut command);
+    let mut child = command
+        .args(args())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to start the Python engine")?;
+
+    // Closing stdin after the request makes the engine exit once it answers
+    let request = Request { id: 1, method, params };
+    child
+        .stdin
+        .take()
+        .context("Engine stdin unavailable")?
+        .write_all(request.to_line().as_bytes())
+        .context("Failed to send request to the engine")?;
+
+    let mut stderr = child.stderr.take().context("Engine stderr unavailable")?;
+    let stderr_reader = std::thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text);
+        text
+    });
+
+    let stdout = child.stdout.take().context("Engine stdout unavailable")?;
+    let mut answer = None;
+    for line in BufReader::new(stdout).lines().map_while(std::result::Result::ok) {
+        // Skip anything that is not a message, e.g. output from a native library
+        let Ok(message) = Message::parse(&line) else {
+            continue;
+        };
+        match message {
+            Message::Ready { protocol, .. } => check_version(protocol)?,
+            Message::Result { result, .. } => answer = Some(Ok(result)),
+            Message::Error { error, .. } => answer = Some(Err(error)),
+            event => on_event(&event),
+        }
+    }
+
+    let status = child.wait().context("Failed to wait for the engine")?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+
+    match answer {
+        Some(Ok(result)) => Ok(result),
+        Some(Err(error)) => Err(error.into()),
+        None => anyhow::bail!(
+            "The engine exited ({}) without answering:\n{}",
+            status,
+            stderr.trim()
+        ),
+    }
+}
+
+/// Refuse to talk to an engine that speaks a different protocol version
+pub fn check_version(protocol: u32) -> Result<(), EngineError> {
+    if protocol != PROTOCOL_VERSION {
+        return Err(EngineError {
+            code: "protocol_mismatch".to_string(),
+            message: format!(
+                "The Python engine speaks protocol version {}, this CLI expects {}. \
+                 Update the CLI or the engine so they match.",
+                protocol, PROTOCOL_VERSION
+            ),
+            details: Value::Null,
+        });
+    }
+
+    Ok(())
+}
+
+/// Result field of a render-like request naming the produced file
+pub fn result_path(result: &Value) -> Result<&str, EngineError> {
+    result["path"].as_str().ok_or_else(|| EngineError {
+        code: "invalid_result".to_string(),
+        message: "The engine reported success but no output path".to_string(),
+        details: result.clone(),
+    })
+}
@@ -0,0 +1,126 @@
+//! Messages exchanged with `python -m animaforge_engine.protocol`.
+//!
+//! Both directions carry one JSON object per line. The CLI writes requests
+//! to the engine's stdin; the engine answers on stdout with a `ready`
+//! handshake, then any number of `progress`/`output` events per request,
+//! ending with exactly one `result` or `error`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Protocol version this CLI speaks
+pub const PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize)]
+pub struct Request<'a> {
+    pub id: u64,
+    pub method: &'a str,
+    pub params: Value,
+}
+
+impl Request<'_> {
+    /// The request as a single newline-terminated line
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).expect("requests always serialize");
+        line.push('\n');
+        line
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Message {
+    Ready {
+        protocol: u32,
+        version: String,
+    },
+    Progress {
+        id: Option<u64>,
+        stage: String,
+        #[serde(default)]
+        message: Option<String>,
+        /// Process group doing the work, sent with the `started` stage
+        #[serde(default)]
+        pid: Option<u32>,
+    },
+    /// A line printed by manim while rendering
+    Output {
+        id: Option<u64>,
+        stream: String,
+        line: String,
+    },
+    Result {
+        id: Option<u64>,
+        result: Value,
+    },
+    Error {
+        id: Option<u64>,
+        error: EngineError,
+    },
+}
+
+impl Message {
+    pub fn parse(line: &str) -> Result<Self> {
+        serde_json::from_str(line)
+            .context(format!("Unexpected message from engine: {}", line.trim()))
+    }
+}
+
+/// A structured error reported by the engine
+#[derive(Debug, Clone, PartialEq, Deserialize, thiserror::Error)]
+#[error("{message}")]
+pub struct EngineError {
+    /// Machine-readable kind, e.g. `invalid_code` or `render_failed`
+    pub code: String,
+    pub message: String,
+    #[serde(default)]
+    pub details: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_line() {
+        let request = Request {
+            id: 3,
+            method: "validate",
+            params: json!({"file": "scene.py"}),
+        };
+
+        assert_eq!(
+            request.to_line(),
+            "{\"id\":3,\"method\":\"validate\",\"params\":{\"file\":\"scene.py\"}}\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_messages() {
+        assert_eq!(
+            Message::parse(r#"{"type": "ready", "protocol": 1, "version": "0.1.0"}"#).unwrap(),
+            Message::Ready { protocol: 1, version: "0.1.0".to_string() }
+        );
+        assert_eq!(
+            Message::parse(r#"{"id": 1, "type": "progress", "stage": "validating"}"#).unwrap(),
+            Message::Progress { id: Some(1), stage: "validating".to_string(), message: None, pid: None }
+        );
+        assert_eq!(
+            Message::parse(r#"{"id": 1, "type": "result", "result": {"path": "/tmp/a.mp4"}}"#).unwrap(),
+            Message::Result { id: Some(1), result: json!({"path": "/tmp/a.mp4"}) }
+        );
+
+        let Message::Error { id: None, error } = Message::parse(
+            r#"{"id": null, "type": "error", "error": {"code": "invalid_request", "message": "bad"}}"#,
+        )
+        .unwrap() else {
+            panic!("expected an error message");
+        };
+        assert_eq!(error.code, "invalid_request");
+        assert_eq!(error.to_string(), "bad");
+
+        assert!(Message::parse("Traceback (most recent call last):").is_err());
+    }
+}
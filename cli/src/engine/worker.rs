@@ -0,0 +1,283 @@
+//! Client for the long-lived engine worker (`python -m animaforge_engine.worker`).
+//!
+//! The worker keeps a Python interpreter with manim already imported and
+//! listens on a Unix socket in the cache directory. The first command that
+//! needs it starts it; later commands connect to the same process, so only
+//! the first render pays for Python and manim start-up. The worker exits by
+//! itself after `ANIMAFORGE_WORKER_IDLE` seconds (default 600) without clients.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use super::protocol::{Message, Request};
+use crate::utils::process;
+
+/// Module that runs the worker
+const MODULE: &str = "animaforge_engine.worker";
+
+/// Importing manim on a cold start can take a while
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a healthy worker may take to answer a ping
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+const DEFAULT_IDLE_SECS: u64 = 600;
+
+/// The worker closed the connection without answering, i.e. it crashed or was killed
+#[derive(Debug, thiserror::Error)]
+#[error("The engine worker exited unexpectedly")]
+pub struct WorkerLost;
+
+/// Whether renders should use the worker. Set `ANIMAFORGE_WORKER=off` to start
+/// a fresh engine process for every render instead.
+pub fn enabled() -> bool {
+    !std::env::var("ANIMAFORGE_WORKER").is_ok_and(|v| v == "off" || v == "0")
+}
+
+fn runtime_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("animaforge")
+}
+
+pub fn socket_path() -> PathBuf {
+    runtime_dir().join("engine.sock")
+}
+
+/// Worker output goes here, since it has no terminal
+pub fn log_path() -> PathBuf {
+    runtime_dir().join("engine.log")
+}
+
+fn pid_path() -> PathBuf {
+    runtime_dir().join("engine.pid")
+}
+
+fn idle_secs() -> u64 {
+    std::env::var("ANIMAFORGE_WORKER_IDLE")
+        .ok()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(DEFAULT_IDLE_SECS)
+}
+
+/// A connection to the worker
+pub struct Worker {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+    /// Bytes of a message line that has not fully arrived yet
+    pending: Vec<u8>,
+    next_id: u64,
+}
+
+impl Worker {
+    /// Connect to the worker, starting it first if it is not running and
+    /// replacing it if it fails its health check
+    pub fn connect() -> Result<Self> {
+        let path = socket_path();
+
+        if let Ok(mut worker) = Self::open(&path) {
+            if worker.ping().is_ok() {
+                return Ok(worker);
+            }
+        }
+
+        stop_stale(&path);
+        start(&path)?;
+
+        let mut worker = Self::open(&path)?;
+        worker
+            .ping()
+            .context("The engine worker failed its health check")?;
+
+        Ok(worker)
+    }
+
+    fn open(path: &Path) -> Result<Self> {
+        let writer = UnixStream::connect(path).context("Failed to connect to the engine worker")?;
+        let mut worker = Self {
+            reader: BufReader::new(writer.try_clone()?),
+            writer,
+            pending: Vec::new(),
+            next_id: 1,
+        };
+
+        match worker.next_message(Some(HEALTH_TIMEOUT))? {
+            Some(Message::Ready { protocol, .. }) => super::check_version(protocol)?,
+            _ => anyhow::bail!("The engine worker did not greet the CLI"),
+        }
+
+        Ok(worker)
+    }
+
+    /// Health check: the worker answers with its pid, uptime and load
+    pub fn ping(&mut self) -> Result<Value> {
+        let id = self.send("ping", json!({}))?;
+        let deadline = Instant::now() + HEALTH_TIMEOUT;
+
+        while Instant::now() < deadline {
+            match self.next_message(Some(HEALTH_TIMEOUT))? {
+                Some(Message::Result { id: Some(reply), result }) if reply == id => return Ok(result),
+                Some(Message::Error { error, .. }) => return Err(error.into()),
+                _ => {}
+            }
+        }
+
+        anyhow::bail!("The engine worker did not answer a ping within {}s", HEALTH_TIMEOUT.as_secs())
+    }
+
+    /// Send a request and return its id
+    pub fn send(&mut self, method: &str, params: Value) -> Result<u64> {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let request = Request { id, method, params };
+        self.writer
+            .write_all(request.to_line().as_bytes())
+            .map_err(|_| WorkerLost)?;
+
+        Ok(id)
+    }
+
+    /// Next message from the worker, or `None` if nothing arrived within `wait`.
+    /// `None` for `wait` blocks until a message arrives.
+    pub fn next_message(&mut self, wait: Option<Duration>) -> Result<Option<Message>> {
+        self.reader.get_ref().set_read_timeout(wait)?;
+
+        loop {
+            match self.reader.read_until(b'\n', &mut self.pending) {
+                Ok(_) if self.pending.ends_with(b"\n") => {
+                    let line = std::mem::take(&mut self.pending);
+                    // Skip anything that is not a message
+                    if let Ok(message) = Message::parse(&String::from_utf8_lossy(&line)) {
+                        return Ok(Some(message));
+                    }
+                }
+                // End of stream, possibly in the middle of a line
+                Ok(_) => return Err(WorkerLost.into()),
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return Ok(None);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return Err(WorkerLost.into()),
+            }
+        }
+    }
+}
+
+/// Kill a worker that is hung or otherwise unreachable and clear its socket
+fn stop_stale(path: &Path) {
+    if let Some(pid) = fs::read_to_string(pid_path())
+        .ok()
+        .and_then(|pid| pid.trim().parse::<i32>().ok())
+        .filter(|pid| is_worker(*pid, path))
+    {
+        // SAFETY: kill has no memory-safety preconditions
+        unsafe {
+            libc::kill(pid, libc::SIGKILL);
+        }
+    }
+
+    let _ = fs::remove_file(path);
+    let _ = fs::remove_file(pid_path());
+}
+
+/// Whether `pid` is still a worker serving `path`, and not an unrelated
+/// process that was given the pid of one that exited. Without /proc to
+/// check, the process is left alone.
+fn is_worker(pid: i32, path: &Path) -> bool {
+    let Ok(cmdline) = fs::read(format!("/proc/{}/cmdline", pid)) else {
+        return false;
+    };
+    let args: Vec<&[u8]> = cmdline.split(|b| *b == 0).collect();
+    let socket = path.as_os_str().as_encoded_bytes();
+
+    args.contains(&MODULE.as_bytes()) && args.contains(&socket)
+}
+
+/// Start a worker in the background and wait until it accepts connections
+fn start(path: &Path) -> Result<()> {
+    fs::create_dir_all(runtime_dir()).context("Failed to create engine worker directory")?;
+    let log = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path())
+        .context("Failed to open engine worker log")?;
+
+    let mut command = Command::new(super::python());
+    super::configure(&mut command);
+    command
+        .args(["-m", MODULE, "--socket"])
+        .arg(path)
+        .arg("--idle-timeout")
+        .arg(idle_secs().to_string())
+        .arg("--pid-file")
+        .arg(pid_path())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(log);
+    // Its own process group keeps Ctrl-C in this terminal from reaching it
+    process::isolate(&mut command, None, None);
+
+    let mut child = command.spawn().context("Failed to start the engine worker")?;
+    fs::write(pid_path(), child.id().to_string()).context("Failed to record engine worker pid")?;
+
+    let deadline = Instant::now() + STARTUP_TIMEOUT;
+    while Instant::now() < deadline {
+        if UnixStream::connect(path).is_ok() {
+            return Ok(());
+        }
+
+        if let Some(status) = child.try_wait()? {
+            anyhow::bail!(
+                "The engine worker exited during start-up ({}). See {}",
+                status,
+                log_path().display()
+            );
+        }
+
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let _ = child.kill();
+    anyhow::bail!(
+        "The engine worker did not start within {}s. See {}",
+        STARTUP_TIMEOUT.as_secs(),
+        log_path().display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_only_the_worker_for_a_socket() {
+        let socket = std::env::temp_dir().join("animaforge_test_engine.sock");
+        // A stand-in whose command line names the module and the socket
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg("sleep 5; true")
+            .arg(MODULE)
+            .arg(&socket)
+            .spawn()
+            .unwrap();
+        let pid = child.id() as i32;
+        // Let it get past exec
+        std::thread::sleep(Duration::from_millis(200));
+
+        let found = is_worker(pid, &socket);
+        let other_socket = is_worker(pid, Path::new("/tmp/other.sock"));
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert!(found);
+        assert!(!other_socket);
+        assert!(!is_worker(std::process::id() as i32, &socket));
+    }
+}
@@ -165,6 +165,13 @@ impl OllamaClient {
     }
 
     pub async fn list_models(&self) -> Result<Vec<String>> {
+        let models = self.list_model_details().await?;
+
+        Ok(models.into_iter().map(|m| m.name).collect())
+    }
+
+    /// Installed models with their size and details
+    pub async fn list_model_details(&self) -> Result<Vec<ModelInfo>> {
         let url = format!("{}/api/tags", self.endpoint);
 
         let response = self
@@ -179,17 +186,144 @@ impl OllamaClient {
             models: Vec<ModelInfo>,
         }
 
-        #[derive(Deserialize)]
-        struct ModelInfo {
-            name: String,
+        let tags: TagsResponse = response.json().await?;
+
+        Ok(tags.models)
+    }
+
+    /// Details, parameters and prompt template of an installed model
+    pub async fn show_model(&self, name: &str) -> Result<ModelShow> {
+        let url = format!("{}/api/show", self.endpoint);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "name": name }))
+            .send()
+            .await
+            .context("Failed to connect to Ollama")?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            anyhow::bail!(
+                "Model '{}' is not installed. Pull it with: animaforge models pull {}",
+                name,
+                name
+            );
+        }
+        if !response.status().is_success() {
+            anyhow::bail!("Ollama API error: {}", response.status());
         }
 
-        let tags: TagsResponse = response.json().await?;
+        response
+            .json()
+            .await
+            .context("Failed to parse Ollama response")
+    }
+
+    /// Download a model, calling `on_progress` for every status update
+    pub async fn pull_model<F>(&self, name: &str, mut on_progress: F) -> Result<()>
+    where
+        F: FnMut(&PullProgress),
+    {
+        let url = format!("{}/api/pull", self.endpoint);
+
+        let mut response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "name": name, "stream": true }))
+            .send()
+            .await
+            .context("Failed to connect to Ollama")?;
 
-        Ok(tags.models.into_iter().map(|m| m.name).collect())
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Ollama API error ({}): {}", status, error_text.trim());
+        }
+
+        // NDJSON; a line can be split across chunks
+        let mut buffer = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                handle_pull_line(&line, &mut on_progress)?;
+            }
+        }
+        handle_pull_line(&buffer, &mut on_progress)?;
+
+        Ok(())
     }
 }
 
+fn handle_pull_line<F>(line: &[u8], on_progress: &mut F) -> Result<()>
+where
+    F: FnMut(&PullProgress),
+{
+    let line = String::from_utf8_lossy(line);
+    if line.trim().is_empty() {
+        return Ok(());
+    }
+
+    let progress: PullProgress =
+        serde_json::from_str(&line).context("Failed to parse Ollama pull progress")?;
+    if let Some(error) = &progress.error {
+        anyhow::bail!("Pull failed: {}", error);
+    }
+    on_progress(&progress);
+
+    Ok(())
+}
+
+/// An installed model as listed by `/api/tags`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModelInfo {
+    pub name: String,
+    /// Size on disk in bytes
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub modified_at: Option<String>,
+    #[serde(default)]
+    pub details: ModelDetails,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ModelDetails {
+    pub format: Option<String>,
+    pub family: Option<String>,
+    /// e.g. "8.0B"
+    pub parameter_size: Option<String>,
+    /// e.g. "Q4_0"
+    pub quantization_level: Option<String>,
+}
+
+/// Answer of `/api/show`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ModelShow {
+    #[serde(default)]
+    pub details: ModelDetails,
+    /// Modelfile parameters, one `name value` per line
+    pub parameters: Option<String>,
+    pub template: Option<String>,
+    pub license: Option<String>,
+    /// Architecture facts like `llama.context_length`
+    #[serde(default)]
+    pub model_info: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One line of `/api/pull` output
+#[derive(Debug, Clone, Deserialize)]
+pub struct PullProgress {
+    #[serde(default)]
+    pub status: String,
+    /// Layer being downloaded
+    pub digest: Option<String>,
+    pub total: Option<u64>,
+    pub completed: Option<u64>,
+    pub error: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -223,4 +357,19 @@ mod tests {
 
         assert_eq!(extracted, "from manim import *\n\nclass Test(Scene):\n    pass");
     }
+
+    #[test]
+    fn parses_pull_progress_lines() {
+        let mut updates = Vec::new();
+        let line = br#"{"status":"pulling 6a0746a1ec1a","digest":"sha256:6a07","total":4661211808,"completed":1048576}"#;
+        handle_pull_line(line, &mut |p: &PullProgress| updates.push(p.clone())).unwrap();
+        handle_pull_line(b"\n", &mut |p: &PullProgress| updates.push(p.clone())).unwrap();
+
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].total, Some(4661211808));
+        assert_eq!(updates[0].completed, Some(1048576));
+
+        let error = br#"{"error":"pull model manifest: file does not exist"}"#;
+        assert!(handle_pull_line(error, &mut |_: &PullProgress| {}).is_err());
+    }
 }
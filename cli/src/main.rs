@@ -1,14 +1,29 @@
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
+use clap_complete::CompleteEnv;
 use colored::Colorize;
+use std::path::PathBuf;
+use std::process::ExitCode;
 
 mod cli;
 mod commands;
 mod config;
+mod engine;
 mod llm;
+mod manim;
+mod media;
+mod sidecar;
 mod utils;
 
 use cli::{Cli, Commands};
+use commands::init::InitOptions;
+use commands::render::{RenderLimits, RenderOptions, SandboxMode};
+use commands::stitch::StitchOptions;
+use commands::test::TestOptions;
+use config::Config;
+use media::check::CheckOptions;
+use media::mux::MuxOptions;
+use utils::output::{self, ErrorCode};
 
 const LOGO: &str = r#"
     ___          _                ______
@@ -27,31 +42,216 @@ fn print_logo() {
     );
 }
 
+fn main() -> ExitCode {
+    // Answer the shell when it asks for completions (see `animaforge completions`)
+    CompleteEnv::with_factory(Cli::command)
+        .var(commands::completions::COMPLETE_VAR)
+        .complete();
+
+    start()
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn start() -> ExitCode {
+    let matches = Cli::command().get_matches();
+    let command = matches.subcommand_name().unwrap_or_default().to_string();
+    let cli = match Cli::from_arg_matches(&matches) {
+        Ok(cli) => cli,
+        Err(e) => e.exit(),
+    };
+
+    let mode = output::Mode {
+        json: cli.json,
+        quiet: cli.quiet,
+    };
+    if let Err(e) = output::init(mode, cli.no_color) {
+        eprintln!("Error: {:#}", e);
+        return ExitCode::FAILURE;
+    }
+    if let Some(name) = cli.profile.clone() {
+        config::select_profile(name);
+    }
+
+    // Kill running renders (and everything they spawned) on Ctrl-C. When
+    // nothing is running, exit the way the default handler would.
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if !utils::process::cancel_all() {
+                std::process::exit(130);
+            }
+        }
+    });
 
-    // Print logo for most commands (except config get which should be clean)
-    if !matches!(cli.command, Commands::Config { .. }) {
+    // Print logo for most commands (except those whose output is read back)
+    if !output::is_scripted()
+        && !matches!(
+            cli.command,
+            Commands::Config { .. } | Commands::Completions { .. } | Commands::Man { .. }
+        )
+    {
         print_logo();
     }
 
-    match cli.command {
+    let outcome = run(cli.command).await;
+    output::finish(&command, outcome)
+}
+
+async fn run(command: Commands) -> Result<()> {
+    match command {
+        Commands::Init {
+            backend,
+            model,
+            api_key,
+            ollama_endpoint,
+            output_dir,
+            quality,
+            yes,
+            skip_test,
+        } => {
+            let options = InitOptions {
+                backend,
+                model,
+                api_key,
+                ollama_endpoint,
+                output_dir,
+                quality,
+                yes,
+                skip_test,
+            };
+            commands::init::execute(options).await?;
+        }
         Commands::Create { prompt, render, output } => {
             commands::create::execute(prompt, render, output).await?;
         }
-        Commands::Render { file, quality, output } => {
-            commands::render::execute(file, quality, output).await?;
+        Commands::Render {
+            file,
+            quality,
+            target,
+            output,
+            jobs,
+            timeout,
+            cpu_limit,
+            memory_limit,
+            sandbox,
+            no_sandbox,
+            thumbnail_at,
+            gif,
+            no_thumbnail,
+            stitch,
+            order,
+            crossfade,
+            audio,
+            audio_offset,
+            audio_volume,
+            subtitles,
+            burn_subtitles,
+            check,
+            min_duration,
+            max_duration,
+        } => {
+            let mut config = Config::load()?;
+            if let Some(quality) = &quality {
+                config
+                    .set_flag("quality", quality, "--quality")
+                    .map_err(|e| output::with_code(ErrorCode::Config, e))?;
+            }
+            if sandbox {
+                config.set_flag("sandbox", "always", "--sandbox")?;
+            }
+            if no_sandbox {
+                config.set_flag("sandbox", "never", "--no-sandbox")?;
+            }
+            let quality = config.quality().to_string();
+            let sandbox = SandboxMode::from_config(&config);
+            let target = match target {
+                Some(name) => Some((name.clone(), config.target(&name)?)),
+                None => None,
+            };
+            let mut thumbnails = config.thumbnails;
+            if let Some(at) = thumbnail_at {
+                thumbnails.at = at;
+            }
+            thumbnails.gif |= gif;
+            thumbnails.enabled &= !no_thumbnail;
+
+            let limits = RenderLimits {
+                timeout: timeout.map(std::time::Duration::from_secs),
+                cpu_secs: cpu_limit,
+                memory_mb: memory_limit,
+                sandbox,
+            };
+            let options = RenderOptions {
+                quality,
+                output,
+                jobs,
+                limits,
+                thumbnails,
+                stitch: stitch.then_some(StitchOptions { order, crossfade }),
+                mux: MuxOptions {
+                    audio: audio.map(PathBuf::from),
+                    audio_offset,
+                    audio_volume,
+                    subtitles: subtitles.map(PathBuf::from),
+                    burn_subtitles,
+                },
+                target,
+                check: check.then_some(CheckOptions { min_duration, max_duration }),
+            };
+            commands::render::execute(file, options).await?;
+        }
+        Commands::Preview { file, at, fps, resolution, output } => {
+            let sandbox = SandboxMode::from_config(&Config::load()?);
+            commands::preview::execute(file, at, fps, resolution, output, sandbox).await?;
+        }
+        Commands::Validate { file } => {
+            commands::validate::execute(file)?;
+        }
+        Commands::Test {
+            inputs,
+            at,
+            quality,
+            update,
+            tolerance,
+            threshold,
+            goldens,
+            results,
+        } => {
+            let options = TestOptions {
+                at,
+                quality,
+                update,
+                tolerance,
+                threshold,
+                goldens: goldens.map(PathBuf::from),
+                results: PathBuf::from(results),
+                sandbox: SandboxMode::from_config(&Config::load()?),
+            };
+            commands::test::execute(inputs, options).await?;
+        }
+        Commands::Template { name, values, output, render } => {
+            commands::template::execute(name, values, output, render).await?;
         }
         Commands::Config { action } => {
             commands::config::execute(action)?;
         }
+        Commands::Models { action } => {
+            commands::models::execute(action).await?;
+        }
         Commands::Publish { file } => {
             commands::publish::execute(file).await?;
         }
         Commands::Search { query, limit } => {
             commands::search::execute(query, limit).await?;
         }
+        Commands::Doctor => {
+            commands::doctor::execute().await?;
+        }
+        Commands::Completions { shell } => {
+            commands::completions::execute(shell)?;
+        }
+        Commands::Man { out_dir } => {
+            commands::man::execute(out_dir)?;
+        }
     }
 
     Ok(())
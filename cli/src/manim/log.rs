@@ -0,0 +1,82 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Older logs are removed when a new render starts
+const KEEP_LOGS: usize = 50;
+
+/// Full manim output of one render, written as it arrives
+pub struct RenderLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl RenderLog {
+    /// Start a log in `logs_dir()` named after the input file and scene
+    pub fn create(input: &Path, scene_name: &str) -> Result<Self> {
+        let dir = logs_dir();
+        fs::create_dir_all(&dir).context("Failed to create render log directory")?;
+        prune(&dir);
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let stem = input
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "render".to_string());
+        let path = dir.join(format!("{}-{}-{}.log", stem, scene_name, timestamp));
+
+        let file = File::create(&path).context("Failed to create render log")?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Append one line of output. Logging never fails a render.
+    pub fn line(&self, line: &str) {
+        let _ = writeln!(self.file.lock().unwrap(), "{}", line);
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Everything written so far
+    pub fn contents(&self) -> String {
+        let _ = self.file.lock().unwrap().flush();
+        fs::read_to_string(&self.path).unwrap_or_default()
+    }
+}
+
+pub fn logs_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("animaforge")
+        .join("logs")
+}
+
+/// Keep only the newest logs
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut logs: Vec<_> = entries
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "log"))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if logs.len() < KEEP_LOGS {
+        return;
+    }
+
+    logs.sort();
+    for (_, path) in &logs[..logs.len() + 1 - KEEP_LOGS] {
+        let _ = fs::remove_file(path);
+    }
+}
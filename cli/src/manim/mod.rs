@@ -0,0 +1,4 @@
+pub mod log;
+pub mod progress;
+pub mod target;
+pub mod traceback;
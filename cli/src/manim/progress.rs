@@ -0,0 +1,321 @@
+use std::io::{BufRead, BufReader, Read};
+
+/// A progress-relevant line of manim output
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// tqdm bar for one animation, e.g. `Animation 2: Create(Circle):  47%|...`
+    Animation {
+        index: usize,
+        name: String,
+        percent: u64,
+    },
+    /// An animation finished and its partial movie file was written
+    PartialMovie { index: usize },
+    /// An animation was skipped because manim had it cached
+    Cached { index: usize },
+    /// Manim combined the partial movie files into the final output
+    Combining,
+    /// Summary printed at the end: `Played N animations`
+    Played { count: usize },
+}
+
+/// Parse a single line of manim stdout or stderr
+pub fn parse_line(line: &str) -> Option<ProgressEvent> {
+    let line = line.trim();
+
+    if let Some(count) = parse_played(line) {
+        return Some(ProgressEvent::Played { count });
+    }
+
+    if line.contains("Combining to Movie file") {
+        return Some(ProgressEvent::Combining);
+    }
+
+    // Both the tqdm bars and the log lines start with "Animation N", though
+    // log lines carry a timestamp and level before it
+    let start = line.find("Animation ")?;
+    let rest = &line[start + "Animation ".len()..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let index = digits.parse().ok()?;
+    let rest = rest[digits.len()..].trim_start();
+
+    if rest.contains("Partial movie file") {
+        return Some(ProgressEvent::PartialMovie { index });
+    }
+
+    if rest.contains("Using cached data") {
+        return Some(ProgressEvent::Cached { index });
+    }
+
+    // tqdm: "Animation 0: Create(Circle):  47%|####6     | 7/15 [...]"
+    let rest = rest.strip_prefix(':')?;
+    let (label, _) = rest.split_once('|')?;
+    let (name, percent) = label.trim().rsplit_once(' ')?;
+    let percent = percent.strip_suffix('%')?.parse().ok()?;
+
+    Some(ProgressEvent::Animation {
+        index,
+        name: name.trim().trim_end_matches(':').to_string(),
+        percent,
+    })
+}
+
+fn parse_played(line: &str) -> Option<usize> {
+    let rest = line.split("Played ").nth(1)?;
+    let (count, unit) = rest.split_once(' ')?;
+
+    if !unit.starts_with("animation") {
+        return None;
+    }
+
+    count.parse().ok()
+}
+
+/// Overall progress across all animations of a render
+#[derive(Debug)]
+pub struct RenderProgress {
+    /// Expected number of animations; grows if manim plays more than estimated
+    total: usize,
+    /// Number of animations fully rendered
+    done: usize,
+    /// Progress of the animation currently rendering, in percent
+    current: u64,
+    current_name: Option<String>,
+    finished: bool,
+    /// Highest percentage reported so far, so the bar never moves backwards
+    reported: u64,
+}
+
+impl RenderProgress {
+    pub fn new(estimated_total: usize) -> Self {
+        Self {
+            total: estimated_total.max(1),
+            done: 0,
+            current: 0,
+            current_name: None,
+            finished: false,
+            reported: 0,
+        }
+    }
+
+    pub fn update(&mut self, event: &ProgressEvent) {
+        match event {
+            ProgressEvent::Animation { index, name, percent } => {
+                // A bar for animation N means every animation before it is done
+                self.done = self.done.max(*index);
+                self.current = (*percent).min(100);
+                self.current_name = Some(name.clone());
+                self.total = self.total.max(index + 1);
+            }
+            ProgressEvent::PartialMovie { index } | ProgressEvent::Cached { index } => {
+                self.done = self.done.max(index + 1);
+                self.current = 0;
+                self.total = self.total.max(index + 1);
+            }
+            ProgressEvent::Combining => {
+                self.done = self.total;
+                self.current = 0;
+            }
+            ProgressEvent::Played { count } => {
+                self.total = (*count).max(1);
+                self.done = self.total;
+                self.finished = true;
+            }
+        }
+    }
+
+    /// Overall completion in percent. Stays below 100 until manim reports the
+    /// render as finished.
+    pub fn percent(&mut self) -> u64 {
+        let percent = if self.finished {
+            100
+        } else {
+            let done = self.done as f64 + self.current as f64 / 100.0;
+            ((done / self.total as f64) * 100.0).min(99.0) as u64
+        };
+
+        self.reported = self.reported.max(percent);
+        self.reported
+    }
+
+    pub fn message(&self) -> String {
+        if self.finished {
+            return format!("Rendered {} animations", self.total);
+        }
+
+        if self.done >= self.total {
+            return "Combining movie files...".to_string();
+        }
+
+        match &self.current_name {
+            Some(name) => format!("Animation {}/{}: {}", self.done + 1, self.total, name),
+            None => format!("Animation {}/{}", self.done + 1, self.total),
+        }
+    }
+}
+
+/// Estimate how many animations a scene will play by counting `self.play(`
+/// and `self.wait(` calls. Loops make this a lower bound.
+pub fn estimate_animations(source: &str) -> usize {
+    source
+        .lines()
+        .map(|line| line.trim_start())
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.matches("self.play(").count() + line.matches("self.wait(").count())
+        .sum::<usize>()
+        .max(1)
+}
+
+/// Call `f` for every line of `reader`, treating carriage returns as line
+/// breaks since tqdm redraws its bar with `\r`
+pub fn for_each_line<R: Read>(reader: R, mut f: impl FnMut(&str)) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        // Read up to the next '\n'; tqdm output may contain several '\r' redraws
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let text = String::from_utf8_lossy(&buf);
+                for line in text.split(['\r', '\n']) {
+                    if !line.trim().is_empty() {
+                        f(line);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured from `manim render -ql intro.py Intro` (Manim Community v0.18.1)
+    // with stdout piped; rich wraps log records at 80 columns
+    const STDOUT: &str = "Manim Community v0.18.1
+
+[10/19/26 14:02:11] INFO     Animation 0 : Partial movie file   scene_file_writer.py:527
+                             written in
+                             '/tmp/media/videos/intro/480p15/part
+                             ial_movie_files/Intro/1185818338_582
+                             992349_223132457.mp4'
+[10/19/26 14:02:12] INFO     Animation 1 : Using cached data (hash   cairo_renderer.py:88
+                             : 2852726489_4171150329_223132457)
+                    INFO     Animation 2 : Partial movie file   scene_file_writer.py:527
+                             written in
+                             '/tmp/media/videos/intro/480p15/part
+                             ial_movie_files/Intro/2201830969_104
+                             3386281_1740151785.mp4'
+                    INFO     Combining to Movie file.           scene_file_writer.py:617
+                    INFO                                        scene_file_writer.py:737
+                             File ready at
+                             '/tmp/media/videos/intro/480p15/Intr
+                             o.mp4'
+
+                    INFO     Rendered Intro                                 scene.py:247
+                             Played 3 animations
+";
+
+    const STDERR: &str = "\rAnimation 0: Write(Text('AnimaForge')):   0%|          | 0/15 [00:00<?, ?it/s]\
+\rAnimation 0: Write(Text('AnimaForge')):  47%|####6     | 7/15 [00:00<00:00, 65.12it/s]\
+\r                                                                                    \r\n\
+\rAnimation 2: Create(Circle):  33%|###3      | 5/15 [00:00<00:00, 48.90it/s]\
+\rAnimation 2: Create(Circle): 100%|##########| 15/15 [00:00<00:00, 51.02it/s]\
+\r                                                                                    \r\n";
+
+    fn events(output: &str) -> Vec<ProgressEvent> {
+        let mut events = Vec::new();
+        for_each_line(output.as_bytes(), |line| {
+            if let Some(event) = parse_line(line) {
+                events.push(event);
+            }
+        });
+        events
+    }
+
+    #[test]
+    fn test_parse_stdout_log() {
+        assert_eq!(
+            events(STDOUT),
+            vec![
+                ProgressEvent::PartialMovie { index: 0 },
+                ProgressEvent::Cached { index: 1 },
+                ProgressEvent::PartialMovie { index: 2 },
+                ProgressEvent::Combining,
+                ProgressEvent::Played { count: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tqdm_bars() {
+        let events = events(STDERR);
+
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[1],
+            ProgressEvent::Animation {
+                index: 0,
+                name: "Write(Text('AnimaForge'))".to_string(),
+                percent: 47,
+            }
+        );
+        assert_eq!(
+            events[3],
+            ProgressEvent::Animation {
+                index: 2,
+                name: "Create(Circle)".to_string(),
+                percent: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_progress_is_monotonic_across_animations() {
+        let mut progress = RenderProgress::new(3);
+        let mut last = 0;
+
+        // Interleave the two streams the way they arrive in practice
+        let stdout = events(STDOUT);
+        let stderr = events(STDERR);
+        let ordered = [
+            &stderr[0], &stderr[1], &stdout[0], &stdout[1], &stderr[2], &stderr[3],
+            &stdout[2], &stdout[3],
+        ];
+
+        for event in ordered {
+            progress.update(event);
+            let percent = progress.percent();
+            assert!(percent >= last, "progress went backwards: {} -> {}", last, percent);
+            assert!(percent < 100);
+            last = percent;
+        }
+
+        progress.update(&stdout[4]);
+        assert_eq!(progress.percent(), 100);
+    }
+
+    #[test]
+    fn test_progress_grows_total_beyond_estimate() {
+        let mut progress = RenderProgress::new(1);
+
+        progress.update(&ProgressEvent::PartialMovie { index: 0 });
+        progress.update(&ProgressEvent::Animation {
+            index: 1,
+            name: "FadeOut(Square)".to_string(),
+            percent: 50,
+        });
+
+        assert_eq!(progress.message(), "Animation 2/2: FadeOut(Square)");
+    }
+
+    #[test]
+    fn test_estimate_animations() {
+        let source = "class A(Scene):\n    def construct(self):\n        # self.play(skip)\n        self.play(Create(c))\n        self.wait(1)\n        self.play(FadeOut(c))\n";
+        assert_eq!(estimate_animations(source), 3);
+        assert_eq!(estimate_animations("pass"), 1);
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::RenderTarget;
+
+/// Containers a target may ask manim to write
+const FORMATS: &[&str] = &["mp4", "mov", "webm", "gif"];
+
+/// Manim's frame height when nothing else is configured
+const DEFAULT_FRAME_HEIGHT: f64 = 8.0;
+
+/// Parse a WIDTHxHEIGHT resolution like "1080x1920"
+pub fn parse_resolution(resolution: &str) -> Result<(u32, u32)> {
+    let size = resolution
+        .split_once(['x', 'X'])
+        .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+        .filter(|&(w, h): &(u32, u32)| w > 0 && h > 0);
+
+    size.with_context(|| format!("Invalid resolution '{}', expected WIDTHxHEIGHT", resolution))
+}
+
+/// Check a target before anything is rendered with it
+pub fn validate(name: &str, target: &RenderTarget) -> Result<()> {
+    let context = || format!("Invalid render target '{}'", name);
+
+    if let Some(resolution) = &target.resolution {
+        parse_resolution(resolution).with_context(context)?;
+    }
+    if target.fps == Some(0) {
+        return Err(anyhow::anyhow!("fps must be greater than 0")).with_context(context);
+    }
+    if let Some(format) = &target.format {
+        if !FORMATS.contains(&format.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Unsupported format '{}'. Use one of: {}",
+                format,
+                FORMATS.join(", ")
+            ))
+            .with_context(context);
+        }
+    }
+    for size in [target.frame_width, target.frame_height]
+        .into_iter()
+        .flatten()
+    {
+        if !(size > 0.0 && size.is_finite()) {
+            return Err(anyhow::anyhow!(
+                "frame_width and frame_height must be positive"
+            ))
+            .with_context(context);
+        }
+    }
+
+    Ok(())
+}
+
+/// File extension of what manim writes with this target
+pub fn extension(target: &RenderTarget) -> &str {
+    target.format.as_deref().unwrap_or("mp4")
+}
+
+/// Short description for the render header, e.g. "1080x1920, 30fps, webm"
+pub fn summary(target: &RenderTarget) -> String {
+    let mut parts = Vec::new();
+    parts.extend(target.resolution.clone());
+    parts.extend(target.fps.map(|fps| format!("{}fps", fps)));
+    parts.extend(target.format.clone());
+    parts.join(", ")
+}
+
+/// Manim flags for a target. They come after the quality flag so they
+/// override its resolution and frame rate. The frame size goes through a
+/// manim config file, since manim has no command line flag for it.
+pub fn args(name: &str, target: &RenderTarget) -> Result<Vec<String>> {
+    validate(name, target)?;
+
+    let mut args = Vec::new();
+    if let Some(resolution) = &target.resolution {
+        let (width, height) = parse_resolution(resolution)?;
+        args.push("--resolution".to_string());
+        args.push(format!("{},{}", width, height));
+    }
+    if let Some(fps) = target.fps {
+        args.push("--fps".to_string());
+        args.push(fps.to_string());
+    }
+    if let Some(format) = &target.format {
+        args.push("--format".to_string());
+        args.push(format.clone());
+    }
+    if let Some(background) = &target.background {
+        args.push("--background_color".to_string());
+        args.push(background.clone());
+    }
+
+    if let Some(config) = frame_config(target) {
+        let dir = dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("animaforge")
+            .join("targets");
+        fs::create_dir_all(&dir).context("Failed to create target directory")?;
+
+        let path: PathBuf = dir.join(format!("{}.cfg", name));
+        fs::write(&path, config).context("Failed to write manim config for target")?;
+        args.push("--config_file".to_string());
+        args.push(path.display().to_string());
+    }
+
+    Ok(args)
+}
+
+/// Manim config setting the frame size in scene units. Without it manim keeps
+/// its 16:9 frame and a portrait or square video crops the scene.
+fn frame_config(target: &RenderTarget) -> Option<String> {
+    let aspect = target
+        .resolution
+        .as_deref()
+        .and_then(|r| parse_resolution(r).ok())
+        .map(|(w, h)| w as f64 / h as f64);
+
+    let (width, height) = match (target.frame_width, target.frame_height, aspect) {
+        (Some(width), Some(height), _) => (Some(width), Some(height)),
+        (Some(width), None, Some(aspect)) => (Some(width), Some(width / aspect)),
+        (None, height, Some(aspect)) => {
+            let height = height.unwrap_or(DEFAULT_FRAME_HEIGHT);
+            (Some(height * aspect), Some(height))
+        }
+        (width, height, None) => (width, height),
+    };
+
+    if width.is_none() && height.is_none() {
+        return None;
+    }
+
+    let mut config = String::from("[CLI]\n");
+    if let Some(width) = width {
+        config.push_str(&format!("frame_width = {}\n", width));
+    }
+    if let Some(height) = height {
+        config.push_str(&format!("frame_height = {}\n", height));
+    }
+    Some(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_follows_resolution_aspect() {
+        let shorts = RenderTarget {
+            resolution: Some("1080x1920".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            frame_config(&shorts).unwrap(),
+            "[CLI]\nframe_width = 4.5\nframe_height = 8\n"
+        );
+
+        let square = RenderTarget {
+            resolution: Some("1080x1080".to_string()),
+            frame_width: Some(10.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            frame_config(&square).unwrap(),
+            "[CLI]\nframe_width = 10\nframe_height = 10\n"
+        );
+
+        assert_eq!(frame_config(&RenderTarget::default()), None);
+    }
+
+    #[test]
+    fn rejects_invalid_targets() {
+        assert_eq!(parse_resolution("1920x1080").unwrap(), (1920, 1080));
+        assert!(parse_resolution("1920,1080").is_err());
+        assert!(parse_resolution("0x1080").is_err());
+
+        let target = RenderTarget {
+            format: Some("avi".to_string()),
+            ..Default::default()
+        };
+        assert!(validate("bad", &target).is_err());
+    }
+}
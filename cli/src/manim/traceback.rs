@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::Path;
+
+/// One `File "...", line N, in function` entry of a Python traceback
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub file: String,
+    pub line: usize,
+    /// Missing for syntax errors, which point at a line but no function
+    pub function: Option<String>,
+}
+
+/// The last traceback in a render's output
+#[derive(Debug, Clone, PartialEq)]
+pub struct Traceback {
+    /// Outermost call first, like Python prints them
+    pub frames: Vec<Frame>,
+    /// Final line, e.g. `NameError: name 'Foo' is not defined`
+    pub exception: String,
+}
+
+impl Traceback {
+    /// Innermost frame inside `file`, i.e. where the user's code failed
+    pub fn frame_in(&self, file: &Path) -> Option<&Frame> {
+        let target = resolve(file)?;
+        self.frames
+            .iter()
+            .rev()
+            .find(|frame| resolve(Path::new(&frame.file)).is_some_and(|path| path == target))
+    }
+}
+
+fn resolve(path: &Path) -> Option<std::path::PathBuf> {
+    fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .ok()
+}
+
+/// Find the last Python traceback in manim's output. Handles both the plain
+/// format and the boxed one manim prints through rich.
+pub fn parse(output: &str) -> Option<Traceback> {
+    let lines: Vec<&str> = output.lines().collect();
+    let start = lines
+        .iter()
+        .rposition(|line| line.contains("Traceback (most recent call last)"))?;
+
+    let mut frames = Vec::new();
+    for line in &lines[start + 1..] {
+        let text = line.trim_matches(|c: char| c.is_whitespace() || "│╭╮╰╯─".contains(c));
+
+        if let Some(frame) = parse_plain_frame(text).or_else(|| parse_rich_frame(text)) {
+            frames.push(frame);
+        } else if is_exception(line) {
+            return Some(Traceback {
+                frames,
+                exception: line.trim_end().to_string(),
+            });
+        }
+    }
+
+    None
+}
+
+/// `File "scene.py", line 12, in construct`
+fn parse_plain_frame(text: &str) -> Option<Frame> {
+    let rest = text.strip_prefix("File \"")?;
+    let (file, rest) = rest.split_once('"')?;
+    let rest = rest.strip_prefix(", line ")?;
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let function = rest[digits.len()..]
+        .strip_prefix(", in ")
+        .map(|function| function.trim().to_string());
+
+    Some(Frame {
+        file: file.to_string(),
+        line: digits.parse().ok()?,
+        function,
+    })
+}
+
+/// `/path/to/scene.py:12 in construct`
+fn parse_rich_frame(text: &str) -> Option<Frame> {
+    let (location, function) = text.rsplit_once(" in ")?;
+    let (file, line) = location.rsplit_once(':')?;
+    if !file.ends_with(".py") {
+        return None;
+    }
+
+    Some(Frame {
+        file: file.to_string(),
+        line: line.parse().ok()?,
+        function: Some(function.trim().to_string()),
+    })
+}
+
+/// An unindented `Name: message` or bare `Name` line ends a traceback
+fn is_exception(line: &str) -> bool {
+    if line.starts_with(char::is_whitespace) || line.contains("Traceback") {
+        return false;
+    }
+
+    let name = line.split(':').next().unwrap_or_default().trim_end();
+    !name.is_empty()
+        && name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Lines around `line` (1-based) with their numbers, `context` on each side
+pub fn snippet(source: &str, line: usize, context: usize) -> Vec<(usize, &str)> {
+    let first = line.saturating_sub(context).max(1);
+    source
+        .lines()
+        .enumerate()
+        .map(|(i, text)| (i + 1, text))
+        .skip(first - 1)
+        .take(line + context + 1 - first)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_traceback() {
+        let output = "\
+Animation 0: Create(Circle)
+Traceback (most recent call last):
+  File \"/usr/lib/manim/scene/scene.py\", line 229, in render
+    self.construct()
+  File \"/tmp/broken.py\", line 4, in construct
+    self.play(Foo())
+NameError: name 'Foo' is not defined
+";
+        let traceback = parse(output).unwrap();
+        assert_eq!(traceback.exception, "NameError: name 'Foo' is not defined");
+        assert_eq!(traceback.frames.len(), 2);
+        assert_eq!(
+            traceback.frames[1],
+            Frame {
+                file: "/tmp/broken.py".to_string(),
+                line: 4,
+                function: Some("construct".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_rich_traceback_and_syntax_errors() {
+        let rich = "\
+╭──────────── Traceback (most recent call last) ────────────╮
+│ /tmp/broken.py:4 in construct                             │
+│                                                           │
+│ ❱ 4 │   │   self.play(Foo())                              │
+╰───────────────────────────────────────────────────────────╯
+NameError: name 'Foo' is not defined
+";
+        let traceback = parse(rich).unwrap();
+        assert_eq!(traceback.frames[0].file, "/tmp/broken.py");
+        assert_eq!(traceback.frames[0].line, 4);
+        assert_eq!(traceback.exception, "NameError: name 'Foo' is not defined");
+
+        let syntax = "\
+Traceback (most recent call last):
+  File \"/tmp/broken.py\", line 3
+    def construct(self
+                 ^
+SyntaxError: '(' was never closed
+";
+        let traceback = parse(syntax).unwrap();
+        assert_eq!(traceback.frames[0].function, None);
+        assert_eq!(traceback.exception, "SyntaxError: '(' was never closed");
+
+        assert_eq!(parse("Rendering failed\n"), None);
+    }
+
+    #[test]
+    fn snippet_clamps_to_file() {
+        let source = "a\nb\nc\nd\ne";
+        assert_eq!(snippet(source, 1, 2), vec![(1, "a"), (2, "b"), (3, "c")]);
+        assert_eq!(snippet(source, 4, 1), vec![(3, "c"), (4, "d"), (5, "e")]);
+        assert_eq!(snippet(source, 5, 2), vec![(3, "c"), (4, "d"), (5, "e")]);
+    }
+}
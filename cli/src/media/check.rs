@@ -0,0 +1,423 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::media::compare::{self, Image};
+use crate::utils::ffmpeg;
+
+/// Frames sampled per second, fewer for long videos
+const SAMPLE_FPS: f64 = 2.0;
+const MAX_SAMPLES: f64 = 120.0;
+
+/// Sampled frames are scaled down to this width before analysis
+const SAMPLE_WIDTH: u32 = 160;
+
+/// A frame whose brightness varies less than this is one flat color
+const UNIFORM_STDDEV: f64 = 2.0;
+
+/// A uniform frame darker than this counts as black
+const BLACK_BRIGHTNESS: f64 = 16.0;
+
+/// Share of pixels in percent that may change between two "static" frames,
+/// enough to absorb compression noise
+const STATIC_PERCENT: f64 = 0.1;
+
+/// Longest stretch without visible change before it is worth a warning
+const STATIC_WARN_SECS: f64 = 5.0;
+
+/// Per-pixel threshold (0 to 1) for telling two sampled frames apart
+const STATIC_THRESHOLD: f64 = 0.05;
+
+/// Color difference (0 to 1) that separates content from the background
+const CONTENT_THRESHOLD: f64 = 0.15;
+
+/// Expected length of the video
+#[derive(Debug, Clone, Default)]
+pub struct CheckOptions {
+    pub min_duration: Option<f64>,
+    pub max_duration: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub verdict: Verdict,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(name: &'static str, verdict: Verdict, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            verdict,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub results: Vec<CheckResult>,
+}
+
+impl CheckReport {
+    /// Worst verdict of all checks
+    pub fn verdict(&self) -> Verdict {
+        self.results
+            .iter()
+            .map(|r| r.verdict)
+            .max()
+            .unwrap_or(Verdict::Pass)
+    }
+
+    pub fn print(&self) {
+        println!("\n{}", "Quality check:".bright_yellow().bold());
+        for result in &self.results {
+            let mark = match result.verdict {
+                Verdict::Pass => "✓".green(),
+                Verdict::Warn => "⚠".yellow(),
+                Verdict::Fail => "✗".red(),
+            };
+            println!("  {} {:<10} {}", mark, result.name, result.detail);
+        }
+
+        let summary = match self.verdict() {
+            Verdict::Pass => "PASS".green().bold(),
+            Verdict::Warn => "WARN".yellow().bold(),
+            Verdict::Fail => "FAIL".red().bold(),
+        };
+        println!("  {} {}", "Result:".bright_yellow(), summary);
+    }
+}
+
+/// Sample frames from a rendered video and look for common problems with
+/// generated animations: blank output, long stretches where nothing moves,
+/// content running off the frame, and a length outside what was expected.
+pub fn run(video: &Path, options: &CheckOptions) -> Result<CheckReport> {
+    ffmpeg::check_ffmpeg()?;
+    let duration = ffmpeg::duration(video)?;
+
+    let fps = SAMPLE_FPS.min(MAX_SAMPLES / duration.max(1.0));
+    let dir = std::env::temp_dir().join(format!("animaforge-check-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let frames = ffmpeg::sample_frames(video, fps, SAMPLE_WIDTH, &dir).and_then(|paths| {
+        paths
+            .iter()
+            .map(|path| compare::load_png(path))
+            .collect::<Result<Vec<_>>>()
+    });
+    let _ = fs::remove_dir_all(&dir);
+    let frames = frames.context("Failed to sample frames for the quality check")?;
+
+    Ok(CheckReport {
+        results: vec![
+            check_duration(duration, options),
+            check_blank(&frames, fps),
+            check_static(&frames, fps),
+            check_edges(&frames, fps),
+        ],
+    })
+}
+
+fn check_duration(duration: f64, options: &CheckOptions) -> CheckResult {
+    let length = format!("{:.1}s", duration);
+
+    match (options.min_duration, options.max_duration) {
+        (Some(min), _) if duration < min => CheckResult::new(
+            "duration",
+            Verdict::Fail,
+            format!("{} is shorter than the expected {:.1}s", length, min),
+        ),
+        (_, Some(max)) if duration > max => CheckResult::new(
+            "duration",
+            Verdict::Fail,
+            format!("{} is longer than the expected {:.1}s", length, max),
+        ),
+        _ if duration < 1.0 => CheckResult::new(
+            "duration",
+            Verdict::Warn,
+            format!("{} is very short", length),
+        ),
+        _ => CheckResult::new("duration", Verdict::Pass, length),
+    }
+}
+
+fn check_blank(frames: &[Image], fps: f64) -> CheckResult {
+    let uniform: Vec<usize> = (0..frames.len())
+        .filter(|&i| is_uniform(&frames[i]))
+        .collect();
+
+    if frames.is_empty() || uniform.len() == frames.len() {
+        let color = if frames.iter().all(is_black) {
+            "black"
+        } else {
+            "one flat color"
+        };
+        return CheckResult::new(
+            "blank",
+            Verdict::Fail,
+            format!("every sampled frame is {}", color),
+        );
+    }
+
+    // A blank first or last frame is a normal fade in or out
+    let inner: Vec<usize> = uniform
+        .into_iter()
+        .filter(|&i| i != 0 && i != frames.len() - 1)
+        .collect();
+    if inner.is_empty() {
+        return CheckResult::new("blank", Verdict::Pass, "no blank frames");
+    }
+
+    CheckResult::new(
+        "blank",
+        Verdict::Warn,
+        format!(
+            "{} blank frame{} at {}",
+            inner.len(),
+            if inner.len() == 1 { "" } else { "s" },
+            timestamps(&inner, fps)
+        ),
+    )
+}
+
+fn check_static(frames: &[Image], fps: f64) -> CheckResult {
+    if frames.len() < 2 {
+        return CheckResult::new("static", Verdict::Pass, "too short to judge");
+    }
+
+    // Longest run of frames that look the same as the one before
+    let (mut longest, mut longest_start) = (0, 0);
+    let mut run = 0;
+    for i in 1..frames.len() {
+        let same = compare::compare(&frames[i - 1], &frames[i], STATIC_THRESHOLD)
+            .map(|c| c.percent() <= STATIC_PERCENT)
+            .unwrap_or(false);
+        run = if same { run + 1 } else { 0 };
+        if run > longest {
+            longest = run;
+            longest_start = i - run;
+        }
+    }
+
+    let secs = longest as f64 / fps;
+    if longest == frames.len() - 1 {
+        return CheckResult::new("static", Verdict::Fail, "nothing moves in the whole video");
+    }
+    if secs >= STATIC_WARN_SECS {
+        let start = longest_start as f64 / fps;
+        return CheckResult::new(
+            "static",
+            Verdict::Warn,
+            format!(
+                "nothing moves for {:.1}s ({:.1}s to {:.1}s)",
+                secs,
+                start,
+                start + secs
+            ),
+        );
+    }
+
+    CheckResult::new(
+        "static",
+        Verdict::Pass,
+        format!("longest still stretch {:.1}s", secs),
+    )
+}
+
+fn check_edges(frames: &[Image], fps: f64) -> CheckResult {
+    let mut touching = Vec::new();
+    let mut sides: Vec<&str> = Vec::new();
+    for (i, frame) in frames.iter().enumerate() {
+        let edges = edges_with_content(frame);
+        if !edges.is_empty() {
+            touching.push(i);
+            for side in edges {
+                if !sides.contains(&side) {
+                    sides.push(side);
+                }
+            }
+        }
+    }
+
+    if touching.is_empty() {
+        return CheckResult::new("edges", Verdict::Pass, "content stays inside the frame");
+    }
+
+    CheckResult::new(
+        "edges",
+        Verdict::Warn,
+        format!(
+            "content touches the {} edge in {} of {} frames, from {}",
+            sides.join("/"),
+            touching.len(),
+            frames.len(),
+            timestamps(&touching[..1], fps)
+        ),
+    )
+}
+
+fn brightness_stats(frame: &Image) -> (f64, f64) {
+    let n = frame.pixels.len().max(1) as f64;
+    let values: Vec<f64> = frame
+        .pixels
+        .iter()
+        .map(|p| compare::brightness(*p))
+        .collect();
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+fn is_uniform(frame: &Image) -> bool {
+    brightness_stats(frame).1 < UNIFORM_STDDEV
+}
+
+fn is_black(frame: &Image) -> bool {
+    let (mean, stddev) = brightness_stats(frame);
+    stddev < UNIFORM_STDDEV && mean < BLACK_BRIGHTNESS
+}
+
+/// The most common color, taken to be the background
+fn background(frame: &Image) -> [u8; 4] {
+    let mut counts: HashMap<[u8; 4], usize> = HashMap::new();
+    for pixel in &frame.pixels {
+        // Coarse buckets so compression noise does not split the background
+        *counts.entry(pixel.map(|c| c & 0xf0)).or_default() += 1;
+    }
+
+    let bucket = counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(bucket, _)| bucket)
+        .unwrap_or([0, 0, 0, 255]);
+    frame
+        .pixels
+        .iter()
+        .copied()
+        .find(|p| p.map(|c| c & 0xf0) == bucket)
+        .unwrap_or(bucket)
+}
+
+/// Sides of the frame where something other than background reaches the border
+fn edges_with_content(frame: &Image) -> Vec<&'static str> {
+    let (width, height) = (frame.width as usize, frame.height as usize);
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let bg = background(frame);
+    let content =
+        |x: usize, y: usize| compare::differs(frame.pixels[y * width + x], bg, CONTENT_THRESHOLD);
+    let row = |y| (0..width).any(|x| content(x, y));
+    let column = |x| (0..height).any(|y| content(x, y));
+
+    [
+        ("top", row(0)),
+        ("bottom", row(height - 1)),
+        ("left", column(0)),
+        ("right", column(width - 1)),
+    ]
+    .into_iter()
+    .filter(|(_, touches)| *touches)
+    .map(|(side, _)| side)
+    .collect()
+}
+
+/// "1.5s, 2.0s" for sample indices, shortened after a few
+fn timestamps(indices: &[usize], fps: f64) -> String {
+    let mut listed: Vec<String> = indices
+        .iter()
+        .take(4)
+        .map(|&i| format!("{:.1}s", i as f64 / fps))
+        .collect();
+    if indices.len() > 4 {
+        listed.push("...".to_string());
+    }
+    listed.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10x10 black frame with a white square from `x0,y0` to `x1,y1` (exclusive)
+    fn frame(square: Option<(usize, usize, usize, usize)>) -> Image {
+        let pixels = (0..100)
+            .map(|i| {
+                let (x, y) = (i % 10, i / 10);
+                match square {
+                    Some((x0, y0, x1, y1)) if (x0..x1).contains(&x) && (y0..y1).contains(&y) => {
+                        [255, 255, 255, 255]
+                    }
+                    _ => [0, 0, 0, 255],
+                }
+            })
+            .collect();
+        Image {
+            width: 10,
+            height: 10,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn flags_blank_and_static_videos() {
+        let blank = vec![frame(None); 4];
+        assert_eq!(check_blank(&blank, 2.0).verdict, Verdict::Fail);
+        assert_eq!(check_static(&blank, 2.0).verdict, Verdict::Fail);
+
+        // A black first frame before anything appears is fine
+        let moving: Vec<Image> = (0..4)
+            .map(|i| {
+                if i == 0 {
+                    frame(None)
+                } else {
+                    frame(Some((i, 3, i + 3, 6)))
+                }
+            })
+            .collect();
+        assert_eq!(check_blank(&moving, 2.0).verdict, Verdict::Pass);
+        assert_eq!(check_static(&moving, 2.0).verdict, Verdict::Pass);
+
+        let mut paused = moving.clone();
+        paused.extend(vec![frame(Some((3, 3, 6, 6))); 12]);
+        assert_eq!(check_static(&paused, 2.0).verdict, Verdict::Warn);
+    }
+
+    #[test]
+    fn finds_content_on_edges() {
+        assert!(edges_with_content(&frame(Some((3, 3, 6, 6)))).is_empty());
+        assert_eq!(
+            edges_with_content(&frame(Some((0, 3, 4, 10)))),
+            vec!["bottom", "left"]
+        );
+    }
+
+    #[test]
+    fn duration_range() {
+        let options = CheckOptions {
+            min_duration: Some(5.0),
+            max_duration: Some(10.0),
+        };
+        assert_eq!(check_duration(3.0, &options).verdict, Verdict::Fail);
+        assert_eq!(check_duration(7.0, &options).verdict, Verdict::Pass);
+        assert_eq!(check_duration(12.0, &options).verdict, Verdict::Fail);
+        assert_eq!(
+            check_duration(0.5, &CheckOptions::default()).verdict,
+            Verdict::Warn
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Largest possible YIQ color difference, between black and white
+const MAX_DELTA: f64 = 35215.0;
+
+/// An 8-bit RGBA image
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+/// Result of comparing a frame to its golden
+#[derive(Debug)]
+pub struct Comparison {
+    /// Pixels that differ by more than the threshold
+    pub different: usize,
+    pub total: usize,
+    /// The golden in faded gray with differing pixels in red
+    pub diff: Image,
+}
+
+impl Comparison {
+    /// Share of differing pixels in percent
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.different as f64 * 100.0 / self.total as f64
+    }
+}
+
+pub fn load_png(path: &Path) -> Result<Image> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder
+        .read_info()
+        .with_context(|| format!("{} is not a valid PNG", path.display()))?;
+
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .with_context(|| format!("Failed to decode {}", path.display()))?;
+    let data = &buffer[..info.buffer_size()];
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => data
+            .chunks_exact(4)
+            .map(|p| [p[0], p[1], p[2], p[3]])
+            .collect(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => data.iter().map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => anyhow::bail!("Unexpected palette image in {}", path.display()),
+    };
+
+    Ok(Image {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+pub fn save_png(image: &Image, path: &Path) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), image.width, image.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = image.pixels.iter().flatten().copied().collect();
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Compare two images pixel by pixel in YIQ space, which weighs brightness
+/// over hue roughly the way the eye does. `threshold` (0 to 1) is how far
+/// apart two pixels may be before they count as different, so anti-aliasing
+/// noise from a renderer update does not fail a test.
+pub fn compare(golden: &Image, actual: &Image, threshold: f64) -> Result<Comparison> {
+    if (golden.width, golden.height) != (actual.width, actual.height) {
+        anyhow::bail!(
+            "Frame size changed from {}x{} to {}x{}",
+            golden.width,
+            golden.height,
+            actual.width,
+            actual.height
+        );
+    }
+
+    let mut different = 0;
+    let pixels = golden
+        .pixels
+        .iter()
+        .zip(&actual.pixels)
+        .map(|(a, b)| {
+            if differs(*a, *b, threshold) {
+                different += 1;
+                [255, 0, 0, 255]
+            } else {
+                // Faded gray keeps the layout readable behind the red
+                let gray = (255.0 - (255.0 - luma(blend(*a))) * 0.1) as u8;
+                [gray, gray, gray, 255]
+            }
+        })
+        .collect();
+
+    Ok(Comparison {
+        different,
+        total: golden.pixels.len(),
+        diff: Image {
+            width: golden.width,
+            height: golden.height,
+            pixels,
+        },
+    })
+}
+
+/// Whether two pixels look different; `threshold` as in [`compare`]
+pub fn differs(a: [u8; 4], b: [u8; 4], threshold: f64) -> bool {
+    delta(a, b) > MAX_DELTA * threshold * threshold
+}
+
+/// Brightness of a pixel from 0 to 255
+pub fn brightness(pixel: [u8; 4]) -> f64 {
+    luma(blend(pixel))
+}
+
+/// Composite over white so transparent pixels compare by what is seen
+fn blend([r, g, b, a]: [u8; 4]) -> [f64; 3] {
+    let alpha = a as f64 / 255.0;
+    [r, g, b].map(|c| 255.0 + (c as f64 - 255.0) * alpha)
+}
+
+fn luma([r, g, b]: [f64; 3]) -> f64 {
+    r * 0.29889531 + g * 0.58662247 + b * 0.11448223
+}
+
+/// Squared perceptual distance between two pixels
+fn delta(a: [u8; 4], b: [u8; 4]) -> f64 {
+    let [r1, g1, b1] = blend(a);
+    let [r2, g2, b2] = blend(b);
+
+    let y = luma([r1, g1, b1]) - luma([r2, g2, b2]);
+    let i = (r1 - r2) * 0.59597799 - (g1 - g2) * 0.27417610 - (b1 - b2) * 0.32180189;
+    let q = (r1 - r2) * 0.21147017 - (g1 - g2) * 0.52261711 + (b1 - b2) * 0.31114694;
+
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(pixels: Vec<[u8; 4]>) -> Image {
+        Image {
+            width: pixels.len() as u32,
+            height: 1,
+            pixels,
+        }
+    }
+
+    #[test]
+    fn tolerates_small_color_shifts() {
+        let golden = image(vec![
+            [0, 0, 0, 255],
+            [200, 50, 50, 255],
+            [255, 255, 255, 255],
+        ]);
+        let actual = image(vec![[3, 3, 3, 255], [200, 50, 50, 255], [0, 0, 0, 255]]);
+
+        let comparison = compare(&golden, &actual, 0.1).unwrap();
+        assert_eq!(comparison.different, 1);
+        assert_eq!(comparison.diff.pixels[2], [255, 0, 0, 255]);
+        assert!((comparison.percent() - 33.33).abs() < 0.01);
+
+        assert_eq!(compare(&golden, &actual, 0.0).unwrap().different, 2);
+        assert!(compare(&golden, &image(vec![[0, 0, 0, 255]]), 0.1).is_err());
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("animaforge-compare-{}.png", std::process::id()));
+        let original = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![[10, 20, 30, 255], [0, 0, 0, 0]],
+        };
+
+        save_png(&original, &path).unwrap();
+        let loaded = load_png(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded, original);
+    }
+}
@@ -0,0 +1,54 @@
+pub mod check;
+pub mod compare;
+pub mod mux;
+pub mod probe;
+pub mod thumbnail;
+
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+use crate::config::ThumbnailConfig;
+use crate::sidecar::{self, RenderArtifacts};
+
+/// Probe a freshly rendered `video`, make its thumbnails, and record
+/// everything in the sidecar of `animation`.
+///
+/// Probe and thumbnail problems never fail the render; they are returned as
+/// warnings alongside the artifacts that were recorded.
+pub fn process_render(
+    animation: &Path,
+    video: &Path,
+    thumbnails: &ThumbnailConfig,
+) -> Result<(RenderArtifacts, Vec<String>)> {
+    let mut warnings = Vec::new();
+
+    let media = match probe::probe(video) {
+        Ok(info) => Some(info),
+        Err(e) => {
+            warnings.push(format!("Could not read video details: {}", e));
+            None
+        }
+    };
+
+    let duration = media.as_ref().map(|m| m.duration_secs);
+    let (thumbnail, preview_gif) = thumbnail::generate(video, thumbnails, duration, &mut warnings);
+
+    let artifacts = RenderArtifacts {
+        video: absolute(video),
+        thumbnail: thumbnail.as_deref().map(absolute),
+        preview_gif: preview_gif.as_deref().map(absolute),
+        media,
+    };
+
+    sidecar::record_render(animation, artifacts.clone())?;
+
+    Ok((artifacts, warnings))
+}
+
+fn absolute(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .display()
+        .to_string()
+}
@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::media::probe;
+use crate::utils::ffmpeg::{self, AudioInput, SubtitleInput};
+
+/// Largest gap between audio and video length that goes unreported
+const LENGTH_TOLERANCE_SECS: f64 = 0.5;
+
+/// Audio and subtitles to add to a rendered video
+#[derive(Debug, Clone, Default)]
+pub struct MuxOptions {
+    pub audio: Option<PathBuf>,
+    /// Seconds to delay the audio by; negative values skip into it
+    pub audio_offset: f64,
+    pub audio_volume: f64,
+    pub subtitles: Option<PathBuf>,
+    /// Burn captions into the picture instead of adding a subtitle track
+    pub burn_subtitles: bool,
+}
+
+impl MuxOptions {
+    pub fn is_empty(&self) -> bool {
+        self.audio.is_none() && self.subtitles.is_none()
+    }
+
+    /// Check the inputs before spending time on a render
+    pub fn validate(&self, output: &Path) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let container = output
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if !["mp4", "mov", "m4v", "webm"].contains(&container.as_str()) {
+            anyhow::bail!(
+                "Audio and subtitles need a video output (mp4, mov or webm), not '{}'",
+                output.display()
+            );
+        }
+
+        if let Some(audio) = &self.audio {
+            if !audio.is_file() {
+                anyhow::bail!("Audio file not found: {}", audio.display());
+            }
+        }
+
+        if self.audio_volume < 0.0 {
+            anyhow::bail!("Audio volume cannot be negative");
+        }
+
+        if let Some(subtitles) = &self.subtitles {
+            if !subtitles.is_file() {
+                anyhow::bail!("Subtitle file not found: {}", subtitles.display());
+            }
+
+            let extension = subtitles
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase());
+            if !matches!(extension.as_deref(), Some("srt" | "vtt")) {
+                anyhow::bail!("Subtitles must be an .srt or .vtt file");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Add the configured audio and subtitles to `video` in place. Returns
+/// warnings, such as audio that does not match the video's length.
+pub fn apply(video: &Path, options: &MuxOptions) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    if options.is_empty() {
+        return Ok(warnings);
+    }
+
+    let audio = options.audio.as_deref().map(|path| AudioInput {
+        path,
+        offset: options.audio_offset,
+        volume: options.audio_volume,
+    });
+    let subtitles = options.subtitles.as_deref().map(|path| SubtitleInput {
+        path,
+        burn: options.burn_subtitles,
+    });
+
+    if let Some(audio) = &audio {
+        match (probe::probe(video), ffmpeg::duration(audio.path)) {
+            (Ok(info), Ok(length)) => {
+                if let Some(warning) = length_mismatch(info.duration_secs, length, audio.offset) {
+                    warnings.push(warning);
+                }
+            }
+            (_, Err(e)) | (Err(e), _) => {
+                warnings.push(format!("Could not compare audio and video length: {}", e));
+            }
+        }
+    }
+
+    let stem = video
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "animation".to_string());
+    let extension = video
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_else(|| "mp4".to_string());
+    let muxed = video.with_file_name(format!(".{}_muxed.{}", stem, extension));
+
+    let result = ffmpeg::mux(video, audio.as_ref(), subtitles.as_ref(), &muxed);
+    if result.is_err() {
+        let _ = fs::remove_file(&muxed);
+    }
+    result?;
+
+    fs::rename(&muxed, video).context("Failed to replace video with muxed output")?;
+
+    Ok(warnings)
+}
+
+/// Describe how far the audio (shifted by `offset`) is from the video's length
+fn length_mismatch(video_secs: f64, audio_secs: f64, offset: f64) -> Option<String> {
+    let audio_end = (audio_secs + offset).max(0.0);
+    let difference = audio_end - video_secs;
+
+    if difference.abs() <= LENGTH_TOLERANCE_SECS {
+        return None;
+    }
+
+    let relation = if difference > 0.0 { "longer" } else { "shorter" };
+    Some(format!(
+        "Audio ends at {:.2}s but the video is {:.2}s long ({:.2}s {})",
+        audio_end,
+        video_secs,
+        difference.abs(),
+        relation
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_mismatch() {
+        assert_eq!(length_mismatch(10.0, 10.3, 0.0), None);
+        assert_eq!(length_mismatch(10.0, 8.0, 2.0), None);
+        assert_eq!(
+            length_mismatch(10.0, 12.0, 0.5),
+            Some("Audio ends at 12.50s but the video is 10.00s long (2.50s longer)".to_string())
+        );
+        assert_eq!(
+            length_mismatch(10.0, 12.0, -4.0),
+            Some("Audio ends at 8.00s but the video is 10.00s long (2.00s shorter)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_gif_output() {
+        let options = MuxOptions {
+            subtitles: Some(PathBuf::from("captions.srt")),
+            ..Default::default()
+        };
+
+        assert!(options.validate(Path::new("out.gif")).is_err());
+        assert!(MuxOptions::default().validate(Path::new("out.gif")).is_ok());
+    }
+}
@@ -0,0 +1,324 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::utils::format_size;
+
+/// Basic facts about a rendered video
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MediaInfo {
+    pub duration_secs: f64,
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+    pub codec: String,
+    pub size_bytes: u64,
+}
+
+impl MediaInfo {
+    /// One-line summary, e.g. `5.20s · 854x480 @ 15 fps · h264 · 1.23 MB`
+    pub fn summary(&self) -> String {
+        format!(
+            "{:.2}s · {}x{} @ {} fps · {} · {}",
+            self.duration_secs,
+            self.width,
+            self.height,
+            format_fps(self.frame_rate),
+            self.codec,
+            format_size(self.size_bytes)
+        )
+    }
+}
+
+fn format_fps(fps: f64) -> String {
+    if (fps - fps.round()).abs() < 0.01 {
+        format!("{}", fps.round())
+    } else {
+        format!("{:.2}", fps)
+    }
+}
+
+/// Read duration, dimensions, frame rate and codec from an MP4/MOV file
+pub fn probe(path: &Path) -> Result<MediaInfo> {
+    let mut file = File::open(path)
+        .context(format!("Failed to open {}", path.display()))?;
+    let size_bytes = file.metadata()?.len();
+
+    let moov = read_top_level_box(&mut file, size_bytes, b"moov")?
+        .context("Not an MP4/MOV file (no moov box)")?;
+
+    let mut info = parse_moov(&moov)?;
+    info.size_bytes = size_bytes;
+
+    Ok(info)
+}
+
+/// Seek through the top-level boxes and read the body of the first `kind` box
+fn read_top_level_box(file: &mut File, file_len: u64, kind: &[u8; 4]) -> Result<Option<Vec<u8>>> {
+    let mut offset = 0;
+
+    while offset + 8 <= file_len {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut header = [0u8; 8];
+        file.read_exact(&mut header)?;
+
+        let mut size = u32::from_be_bytes(header[0..4].try_into().unwrap()) as u64;
+        let mut header_len = 8;
+        if size == 1 {
+            let mut large = [0u8; 8];
+            file.read_exact(&mut large)?;
+            size = u64::from_be_bytes(large);
+            header_len = 16;
+        } else if size == 0 {
+            size = file_len - offset;
+        }
+
+        if size < header_len {
+            anyhow::bail!("Corrupt MP4 box at offset {}", offset);
+        }
+        // The size comes from the file, so check it before allocating for it
+        let end = offset
+            .checked_add(size)
+            .filter(|end| *end <= file_len)
+            .with_context(|| {
+                format!("Truncated MP4 file: box at offset {} runs past the end", offset)
+            })?;
+
+        if &header[4..8] == kind {
+            let mut body = vec![0u8; (size - header_len) as usize];
+            file.read_exact(&mut body)
+                .context("Truncated MP4 file")?;
+            return Ok(Some(body));
+        }
+
+        offset = end;
+    }
+
+    Ok(None)
+}
+
+/// Iterate over the child boxes of a container body as (type, body) pairs
+fn boxes(data: &[u8]) -> impl Iterator<Item = (&[u8], &[u8])> {
+    let mut rest = data;
+
+    std::iter::from_fn(move || {
+        if rest.len() < 8 {
+            return None;
+        }
+
+        let mut size = u32::from_be_bytes(rest[0..4].try_into().unwrap()) as usize;
+        let mut header_len = 8;
+        if size == 1 && rest.len() >= 16 {
+            size = u64::from_be_bytes(rest[8..16].try_into().unwrap()) as usize;
+            header_len = 16;
+        } else if size == 0 {
+            size = rest.len();
+        }
+
+        if size < header_len || size > rest.len() {
+            return None;
+        }
+
+        let kind = &rest[4..8];
+        let body = &rest[header_len..size];
+        rest = &rest[size..];
+
+        Some((kind, body))
+    })
+}
+
+fn find<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data).find(|(k, _)| k == kind).map(|(_, body)| body)
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn be_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(at..at + 8)?.try_into().ok()?))
+}
+
+/// (timescale, duration) from an mvhd or mdhd box body
+fn timescale_and_duration(body: &[u8]) -> Option<(u32, u64)> {
+    match body.first()? {
+        // version 1: 64-bit creation/modification times and duration
+        1 => Some((be_u32(body, 20)?, be_u64(body, 24)?)),
+        _ => Some((be_u32(body, 12)?, be_u32(body, 16)? as u64)),
+    }
+}
+
+/// Width and height (16.16 fixed point) from a tkhd box body
+fn track_dimensions(body: &[u8]) -> Option<(u32, u32)> {
+    let at = if *body.first()? == 1 { 88 } else { 76 };
+    Some((be_u32(body, at)? >> 16, be_u32(body, at + 4)? >> 16))
+}
+
+fn parse_moov(moov: &[u8]) -> Result<MediaInfo> {
+    let (timescale, duration) = find(moov, b"mvhd")
+        .and_then(timescale_and_duration)
+        .context("MP4 file has no movie header")?;
+
+    let video = boxes(moov)
+        .filter(|(kind, _)| *kind == b"trak")
+        .find(|(_, trak)| {
+            find(trak, b"mdia")
+                .and_then(|mdia| find(mdia, b"hdlr"))
+                .and_then(|hdlr| hdlr.get(8..12))
+                == Some(b"vide")
+        })
+        .map(|(_, trak)| trak)
+        .context("MP4 file has no video track")?;
+
+    let (width, height) = find(video, b"tkhd")
+        .and_then(track_dimensions)
+        .unwrap_or((0, 0));
+
+    let mdia = find(video, b"mdia").context("Video track has no media box")?;
+    let stbl = find(mdia, b"minf").and_then(|minf| find(minf, b"stbl"));
+
+    let codec = stbl
+        .and_then(|stbl| find(stbl, b"stsd"))
+        .and_then(|stsd| stsd.get(12..16))
+        .map(codec_name)
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // Frame rate = number of samples over the track's own duration
+    let frames: u64 = stbl
+        .and_then(|stbl| find(stbl, b"stts"))
+        .map(|stts| {
+            let entries = be_u32(stts, 4).unwrap_or(0) as usize;
+            (0..entries)
+                .filter_map(|i| be_u32(stts, 8 + i * 8))
+                .map(u64::from)
+                .sum()
+        })
+        .unwrap_or(0);
+    let frame_rate = find(mdia, b"mdhd")
+        .and_then(timescale_and_duration)
+        .filter(|(scale, duration)| *scale > 0 && *duration > 0)
+        .map(|(scale, duration)| frames as f64 / (duration as f64 / scale as f64))
+        .unwrap_or(0.0);
+
+    let duration_secs = if timescale > 0 {
+        duration as f64 / timescale as f64
+    } else {
+        0.0
+    };
+
+    Ok(MediaInfo {
+        duration_secs,
+        width,
+        height,
+        frame_rate,
+        codec,
+        size_bytes: 0,
+    })
+}
+
+fn codec_name(fourcc: &[u8]) -> String {
+    match fourcc {
+        b"avc1" | b"avc3" => "h264".to_string(),
+        b"hvc1" | b"hev1" => "hevc".to_string(),
+        b"vp09" => "vp9".to_string(),
+        b"av01" => "av1".to_string(),
+        b"mp4v" => "mpeg4".to_string(),
+        b"png " => "png".to_string(),
+        b"apch" | b"apcn" | b"apcs" | b"apco" | b"ap4h" => "prores".to_string(),
+        other => String::from_utf8_lossy(other).trim().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(body);
+        out
+    }
+
+    fn full_box(kind: &[u8; 4], fields: &[u32]) -> Vec<u8> {
+        let body: Vec<u8> = fields.iter().flat_map(|f| f.to_be_bytes()).collect();
+        mp4_box(kind, &body)
+    }
+
+    /// Minimal MP4 with one 854x480 h264 track: 75 frames over 5 seconds
+    fn sample_moov() -> Vec<u8> {
+        // version/flags, creation, modification, timescale, duration
+        let mvhd = full_box(b"mvhd", &[0, 0, 0, 1000, 5000]);
+
+        let mut tkhd_fields = vec![0u32; 19];
+        tkhd_fields.push(854 << 16);
+        tkhd_fields.push(480 << 16);
+        let tkhd = full_box(b"tkhd", &tkhd_fields);
+
+        let mdhd = full_box(b"mdhd", &[0, 0, 0, 15360, 76800]);
+        let hdlr = mp4_box(b"hdlr", b"\0\0\0\0\0\0\0\0vide\0\0\0\0");
+        let stsd = mp4_box(b"stsd", b"\0\0\0\0\0\0\0\x01\0\0\0\x10avc1\0\0\0\0");
+        let stts = full_box(b"stts", &[0, 1, 75, 1024]);
+
+        let stbl = mp4_box(b"stbl", &[stsd, stts].concat());
+        let minf = mp4_box(b"minf", &stbl);
+        let mdia = mp4_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = mp4_box(b"trak", &[tkhd, mdia].concat());
+
+        [mvhd, trak].concat()
+    }
+
+    #[test]
+    fn test_parse_moov() {
+        let info = parse_moov(&sample_moov()).unwrap();
+
+        assert_eq!(info.duration_secs, 5.0);
+        assert_eq!((info.width, info.height), (854, 480));
+        assert_eq!(info.frame_rate, 15.0);
+        assert_eq!(info.codec, "h264");
+    }
+
+    #[test]
+    fn test_probe_file() {
+        let path = std::env::temp_dir().join(format!("animaforge_probe_{}.mp4", std::process::id()));
+        let ftyp = mp4_box(b"ftyp", b"isom\0\0\x02\0");
+        let mdat = mp4_box(b"mdat", &[0u8; 64]);
+        let moov = mp4_box(b"moov", &sample_moov());
+        std::fs::write(&path, [ftyp, mdat, moov].concat()).unwrap();
+
+        let info = probe(&path);
+        std::fs::remove_file(&path).unwrap();
+        let info = info.unwrap();
+
+        assert_eq!(info.codec, "h264");
+        assert!(info.size_bytes > 0);
+        assert_eq!(info.summary(), format!("5.00s · 854x480 @ 15 fps · h264 · {}", format_size(info.size_bytes)));
+    }
+
+    #[test]
+    fn test_probe_rejects_non_mp4() {
+        let path = std::env::temp_dir().join(format!("animaforge_probe_{}.txt", std::process::id()));
+        std::fs::write(&path, b"not a video").unwrap();
+
+        let result = probe(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_probe_rejects_oversized_boxes() {
+        let path = std::env::temp_dir().join(format!("animaforge_probe_{}_big.mp4", std::process::id()));
+        let ftyp = mp4_box(b"ftyp", b"isom\0\0\x02\0");
+        // A 64-bit size far beyond the file
+        let moov = [&1u32.to_be_bytes()[..], b"moov", &u64::MAX.to_be_bytes()].concat();
+        std::fs::write(&path, [ftyp, moov].concat()).unwrap();
+
+        let result = probe(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.unwrap_err().to_string().contains("Truncated"));
+    }
+}
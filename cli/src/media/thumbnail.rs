@@ -0,0 +1,81 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::config::ThumbnailConfig;
+use crate::utils::ffmpeg;
+
+/// Make the poster frame and, if configured, a preview GIF next to `video`.
+/// Returns their paths; failures are pushed onto `warnings`.
+pub fn generate(
+    video: &Path,
+    config: &ThumbnailConfig,
+    duration: Option<f64>,
+    warnings: &mut Vec<String>,
+) -> (Option<PathBuf>, Option<PathBuf>) {
+    if !config.enabled {
+        return (None, None);
+    }
+
+    // Short animations may end before the configured timestamp
+    let at = match duration {
+        Some(duration) if config.at >= duration => duration / 2.0,
+        _ => config.at,
+    };
+
+    let thumbnail = match make_thumbnail(video, config, at) {
+        Ok(path) => Some(path),
+        Err(e) => {
+            warnings.push(format!("Thumbnail skipped: {}", e));
+            None
+        }
+    };
+
+    let preview_gif = if config.gif {
+        let path = sibling(video, "preview", "gif");
+        match ffmpeg::make_gif(video, at, config.gif_duration, config.width, &path) {
+            Ok(()) => Some(path),
+            Err(e) => {
+                warnings.push(format!("Preview GIF skipped: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    (thumbnail, preview_gif)
+}
+
+fn make_thumbnail(video: &Path, config: &ThumbnailConfig, at: f64) -> Result<PathBuf> {
+    if !["png", "webp"].contains(&config.format.as_str()) {
+        anyhow::bail!("unsupported format '{}' (use png or webp)", config.format);
+    }
+
+    let path = sibling(video, "thumb", &config.format);
+    ffmpeg::extract_frame(video, at, Some(config.width), &path)?;
+
+    Ok(path)
+}
+
+/// `out/intro.mp4` -> `out/intro_<suffix>.<extension>`
+fn sibling(video: &Path, suffix: &str, extension: &str) -> PathBuf {
+    let stem = video
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "animation".to_string());
+
+    video.with_file_name(format!("{}_{}.{}", stem, suffix, extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sibling() {
+        assert_eq!(
+            sibling(Path::new("out/intro.mp4"), "thumb", "webp"),
+            PathBuf::from("out/intro_thumb.webp")
+        );
+    }
+}
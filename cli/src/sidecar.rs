@@ -0,0 +1,220 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::media::probe::MediaInfo;
+
+/// File in the config directory listing the animation files this machine
+/// produced, one "<sha256> <canonical path>" per line
+const TRUSTED_FILE: &str = "trusted";
+
+/// Metadata stored next to an animation file (`scene.py` -> `scene.animaforge.json`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Sidecar {
+    /// Present when the file was produced by `animaforge create`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation: Option<Generation>,
+
+    /// Artifacts of the last successful render
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render: Option<RenderArtifacts>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Generation {
+    pub prompt: String,
+    pub backend: String,
+    pub model: String,
+    /// Unix timestamp (seconds) of generation
+    pub created_at: u64,
+    /// SHA-256 of the generated code, used to detect later edits
+    pub code_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderArtifacts {
+    pub video: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview_gif: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media: Option<MediaInfo>,
+}
+
+impl Sidecar {
+    pub fn generated(prompt: &str, backend: &str, model: &str, code: &str) -> Self {
+        let created_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        Self {
+            generation: Some(Generation {
+                prompt: prompt.to_string(),
+                backend: backend.to_string(),
+                model: model.to_string(),
+                created_at,
+                code_hash: hash_code(code),
+            }),
+            render: None,
+        }
+    }
+
+    /// Sidecar location for an animation file
+    pub fn path_for(animation: &Path) -> PathBuf {
+        animation.with_extension("animaforge.json")
+    }
+
+    pub fn load(animation: &Path) -> Result<Option<Self>> {
+        let path = Self::path_for(animation);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .context("Failed to read animation sidecar")?;
+        let sidecar = serde_json::from_str(&content)
+            .context("Failed to parse animation sidecar")?;
+
+        Ok(Some(sidecar))
+    }
+
+    pub fn save(&self, animation: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .context("Failed to serialize animation sidecar")?;
+
+        fs::write(Self::path_for(animation), content)
+            .context("Failed to write animation sidecar")?;
+
+        Ok(())
+    }
+}
+
+/// Record the artifacts of a successful render of `animation`
+pub fn record_render(animation: &Path, artifacts: RenderArtifacts) -> Result<()> {
+    let mut sidecar = Sidecar::load(animation)?.unwrap_or_default();
+    sidecar.render = Some(artifacts);
+    sidecar.save(animation)
+}
+
+/// Record `animation` as produced here, in its current content. Only the CLI
+/// writes the list, unlike the sidecar, which travels with the code.
+pub fn trust(animation: &Path) -> Result<()> {
+    trust_in(&Config::config_dir()?.join(TRUSTED_FILE), animation)
+}
+
+/// Whether `animation` was produced by `create` on this machine and has not
+/// been edited since
+pub fn is_generated(animation: &Path) -> bool {
+    Config::config_dir().is_ok_and(|dir| is_trusted_in(&dir.join(TRUSTED_FILE), animation))
+}
+
+fn trust_in(store: &Path, animation: &Path) -> Result<()> {
+    let entry = trust_entry(animation).context("Failed to read the saved animation")?;
+    let path = entry.split_once(' ').map(|(_, path)| path).unwrap_or_default();
+
+    // One entry per file: the latest content replaces the earlier one
+    let mut lines: Vec<String> = fs::read_to_string(store)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| line.split_once(' ').is_some_and(|(_, p)| p != path))
+        .map(str::to_string)
+        .collect();
+    lines.push(entry);
+
+    if let Some(dir) = store.parent() {
+        fs::create_dir_all(dir).context("Failed to create config directory")?;
+    }
+    fs::write(store, lines.join("\n") + "\n").context("Failed to record the animation as trusted")
+}
+
+fn is_trusted_in(store: &Path, animation: &Path) -> bool {
+    let (Ok(entry), Ok(trusted)) = (trust_entry(animation), fs::read_to_string(store)) else {
+        return false;
+    };
+    trusted.lines().any(|line| line == entry)
+}
+
+/// "<sha256> <canonical path>" of the file as it is now
+fn trust_entry(animation: &Path) -> Result<String> {
+    let path = fs::canonicalize(animation)?;
+    let code = fs::read_to_string(&path)?;
+    Ok(format!("{} {}", hash_code(&code), path.display()))
+}
+
+fn hash_code(code: &str) -> String {
+    Sha256::digest(code.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_for() {
+        assert_eq!(
+            Sidecar::path_for(Path::new("animations/intro.py")),
+            PathBuf::from("animations/intro.animaforge.json")
+        );
+    }
+
+    #[test]
+    fn test_hash_code_stable() {
+        assert_eq!(hash_code("abc"), hash_code("abc"));
+        assert_ne!(hash_code("abc"), hash_code("abd"));
+        assert_eq!(hash_code("").len(), 64);
+    }
+
+    #[test]
+    fn test_render_only_sidecar_roundtrip() {
+        let sidecar = Sidecar {
+            generation: None,
+            render: Some(RenderArtifacts {
+                video: "intro.mp4".to_string(),
+                thumbnail: Some("intro_thumb.png".to_string()),
+                preview_gif: None,
+                media: None,
+            }),
+        };
+
+        let json = serde_json::to_string(&sidecar).unwrap();
+        assert!(!json.contains("generation"));
+        assert!(!json.contains("preview_gif"));
+
+        let parsed: Sidecar = serde_json::from_str(&json).unwrap();
+        assert!(parsed.generation.is_none());
+        assert_eq!(parsed.render.unwrap().thumbnail.as_deref(), Some("intro_thumb.png"));
+    }
+
+    #[test]
+    fn test_only_trusts_files_recorded_here() {
+        let dir = std::env::temp_dir().join(format!("animaforge_trust_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let store = dir.join("trusted");
+        let animation = dir.join("scene.py");
+        let code = "from manim import *\n";
+        fs::write(&animation, code).unwrap();
+
+        // A sidecar shipped with the code claims it was generated
+        Sidecar::generated("circle", "ollama", "llama3", code).save(&animation).unwrap();
+        let shipped = is_trusted_in(&store, &animation);
+
+        trust_in(&store, &animation).unwrap();
+        let recorded = is_trusted_in(&store, &animation);
+
+        fs::write(&animation, "import os\n").unwrap();
+        let edited = is_trusted_in(&store, &animation);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!shipped);
+        assert!(recorded);
+        assert!(!edited);
+    }
+}
@@ -0,0 +1,450 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Check that the ffmpeg executable is on PATH
+pub fn check_ffmpeg() -> Result<()> {
+    let ffmpeg_check = Command::new("ffmpeg")
+        .arg("-version")
+        .output();
+
+    if ffmpeg_check.is_err() {
+        anyhow::bail!(
+            "ffmpeg is not installed. Install it from https://ffmpeg.org/download.html\n\
+             (manim also requires it for video output)"
+        );
+    }
+
+    Ok(())
+}
+
+/// Extract a single frame at `timestamp` seconds from a video as an image,
+/// optionally scaled to `width` pixels wide. The format follows the extension.
+pub fn extract_frame(video: &Path, timestamp: f64, width: Option<u32>, output: &Path) -> Result<()> {
+    // Remove any stale frame so the existence check below means something
+    let _ = fs::remove_file(output);
+
+    let mut command = Command::new("ffmpeg");
+    command
+        .args(["-y", "-loglevel", "error", "-ss"])
+        .arg(format!("{:.3}", timestamp))
+        .arg("-i")
+        .arg(video)
+        .args(["-frames:v", "1"]);
+
+    if let Some(width) = width {
+        // -2 keeps the aspect ratio with an even height, which encoders need
+        command.arg("-vf").arg(format!("scale={}:-2", width));
+    }
+
+    let result = command
+        .arg(output)
+        .output()
+        .context("Failed to run ffmpeg")?;
+
+    if !result.status.success() {
+        anyhow::bail!(
+            "ffmpeg failed to extract frame at {}s: {}",
+            timestamp,
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    // ffmpeg exits successfully but writes nothing when seeking past the end
+    if !output.exists() {
+        anyhow::bail!("No frame at {}s (past the end of the video?)", timestamp);
+    }
+
+    Ok(())
+}
+
+/// Write `fps` frames per second of a video as PNGs into `dir`, scaled to
+/// `width` pixels wide. Returns the frames in order.
+pub fn sample_frames(video: &Path, fps: f64, width: u32, dir: &Path) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir).context("Failed to create frame directory")?;
+
+    let result = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-i"])
+        .arg(video)
+        .arg("-vf")
+        .arg(format!("fps={},scale={}:-2", fps, width))
+        .arg(dir.join("frame_%05d.png"))
+        .output()
+        .context("Failed to run ffmpeg")?;
+
+    if !result.status.success() {
+        anyhow::bail!(
+            "ffmpeg failed to sample frames: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    let mut frames: Vec<PathBuf> = fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+        .collect();
+    frames.sort();
+
+    Ok(frames)
+}
+
+/// Make a looping GIF of `duration` seconds starting at `start`, `width` pixels wide
+pub fn make_gif(video: &Path, start: f64, duration: f64, width: u32, output: &Path) -> Result<()> {
+    // Generate a palette from the clip itself; the default GIF palette bands badly
+    let filter = format!(
+        "fps=12,scale={}:-1:flags=lanczos,split[a][b];[a]palettegen[p];[b][p]paletteuse",
+        width
+    );
+
+    let result = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-ss"])
+        .arg(format!("{:.3}", start))
+        .arg("-t")
+        .arg(format!("{:.3}", duration))
+        .arg("-i")
+        .arg(video)
+        .arg("-vf")
+        .arg(filter)
+        .args(["-loop", "0"])
+        .arg(output)
+        .output()
+        .context("Failed to run ffmpeg")?;
+
+    if !result.status.success() {
+        anyhow::bail!(
+            "ffmpeg failed to create GIF: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Join videos with identical encoding back to back without re-encoding
+pub fn concat(inputs: &[PathBuf], output: &Path) -> Result<()> {
+    // The concat demuxer reads its inputs from a list file
+    let list_path = output.with_extension("concat.txt");
+    let list: String = inputs
+        .iter()
+        .map(|path| {
+            let path = fs::canonicalize(path).unwrap_or_else(|_| path.clone());
+            format!("file '{}'\n", path.display().to_string().replace('\'', r"'\''"))
+        })
+        .collect();
+    fs::write(&list_path, list).context("Failed to write ffmpeg concat list")?;
+
+    let result = Command::new("ffmpeg")
+        .args(["-y", "-loglevel", "error", "-f", "concat", "-safe", "0", "-i"])
+        .arg(&list_path)
+        .args(["-c", "copy"])
+        .arg(output)
+        .output()
+        .context("Failed to run ffmpeg");
+    let _ = fs::remove_file(&list_path);
+    let result = result?;
+
+    if !result.status.success() {
+        anyhow::bail!(
+            "ffmpeg failed to join videos: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Join videos with a crossfade of `fade` seconds between consecutive clips.
+/// `durations` are the clip lengths in seconds, in the same order as `inputs`.
+/// Audio is crossfaded too when every clip has some.
+pub fn crossfade(inputs: &[PathBuf], durations: &[f64], fade: f64, output: &Path) -> Result<()> {
+    let with_audio = inputs
+        .iter()
+        .map(|input| has_audio(input))
+        .collect::<Result<Vec<_>>>()?;
+    let audio = with_audio.iter().all(|a| *a);
+    if !audio && with_audio.contains(&true) {
+        anyhow::bail!(
+            "Only some scenes have audio, so they can't be crossfaded. \
+             Join them without --crossfade or add audio to every scene"
+        );
+    }
+
+    let mut command = Command::new("ffmpeg");
+    command.args(["-y", "-loglevel", "error"]);
+    for input in inputs {
+        command.arg("-i").arg(input);
+    }
+
+    command
+        .arg("-filter_complex")
+        .arg(xfade_filter(durations, fade, audio))
+        .args(["-map", "[out]", "-c:v", "libx264", "-pix_fmt", "yuv420p"]);
+    if audio {
+        command.args(["-map", "[aout]", "-c:a", "aac"]);
+    } else {
+        command.arg("-an");
+    }
+
+    let result = command
+        .arg(output)
+        .output()
+        .context("Failed to run ffmpeg")?;
+
+    if !result.status.success() {
+        anyhow::bail!(
+            "ffmpeg failed to crossfade videos: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Chain of xfade filters joining every input into a stream labelled `[out]`,
+/// plus acrossfade filters joining their audio into `[aout]` with `audio`
+fn xfade_filter(durations: &[f64], fade: f64, audio: bool) -> String {
+    if durations.len() < 2 {
+        let video = "[0:v]null[out]".to_string();
+        return if audio { video + ";[0:a]anull[aout]" } else { video };
+    }
+
+    let mut filters = Vec::new();
+    let mut previous = "[0:v]".to_string();
+    let mut offset = 0.0;
+
+    for (i, duration) in durations.iter().enumerate().take(durations.len() - 1) {
+        // Each transition starts `fade` seconds before the joined clip so far ends
+        offset += duration - fade;
+        let label = if i + 2 == durations.len() {
+            "[out]".to_string()
+        } else {
+            format!("[x{}]", i + 1)
+        };
+
+        filters.push(format!(
+            "{}[{}:v]xfade=transition=fade:duration={:.3}:offset={:.3}{}",
+            previous,
+            i + 1,
+            fade,
+            offset,
+            label
+        ));
+        previous = label;
+    }
+
+    if audio {
+        let mut previous = "[0:a]".to_string();
+        for i in 1..durations.len() {
+            let label = if i + 1 == durations.len() {
+                "[aout]".to_string()
+            } else {
+                format!("[a{}]", i)
+            };
+            filters.push(format!("{}[{}:a]acrossfade=d={:.3}{}", previous, i, fade, label));
+            previous = label;
+        }
+    }
+
+    filters.join(";")
+}
+
+/// Whether a media file has an audio stream, read with ffprobe
+pub fn has_audio(path: &Path) -> Result<bool> {
+    let result = Command::new("ffprobe")
+        .args(["-v", "error", "-select_streams", "a"])
+        .args(["-show_entries", "stream=index", "-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .context("Failed to run ffprobe")?;
+
+    if !result.status.success() {
+        anyhow::bail!(
+            "ffprobe could not read {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    Ok(!String::from_utf8_lossy(&result.stdout).trim().is_empty())
+}
+
+/// Length of any media file in seconds, read with ffprobe
+pub fn duration(path: &Path) -> Result<f64> {
+    let result = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "csv=p=0"])
+        .arg(path)
+        .output()
+        .context("Failed to run ffprobe")?;
+
+    if !result.status.success() {
+        anyhow::bail!(
+            "ffprobe could not read {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    String::from_utf8_lossy(&result.stdout)
+        .trim()
+        .parse()
+        .context(format!("ffprobe reported no duration for {}", path.display()))
+}
+
+/// An extra audio track to lay over a video
+pub struct AudioInput<'a> {
+    pub path: &'a Path,
+    /// Seconds to delay the audio by; negative values skip into it
+    pub offset: f64,
+    /// Volume multiplier, 1.0 leaves it unchanged
+    pub volume: f64,
+}
+
+/// A subtitle file to add to a video
+pub struct SubtitleInput<'a> {
+    pub path: &'a Path,
+    /// Draw the captions into the picture instead of adding a subtitle track
+    pub burn: bool,
+}
+
+/// Add an audio track and/or subtitles to `video`, writing the result to `output`
+pub fn mux(
+    video: &Path,
+    audio: Option<&AudioInput>,
+    subtitles: Option<&SubtitleInput>,
+    output: &Path,
+) -> Result<()> {
+    let result = Command::new("ffmpeg")
+        .args(mux_args(video, audio, subtitles, output))
+        .output()
+        .context("Failed to run ffmpeg")?;
+
+    if !result.status.success() {
+        anyhow::bail!(
+            "ffmpeg failed to add audio/subtitles: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+fn mux_args(
+    video: &Path,
+    audio: Option<&AudioInput>,
+    subtitles: Option<&SubtitleInput>,
+    output: &Path,
+) -> Vec<String> {
+    let webm = output.extension().is_some_and(|e| e == "webm");
+    let mut args: Vec<String> = vec!["-y".into(), "-loglevel".into(), "error".into()];
+    args.extend(["-i".into(), video.display().to_string()]);
+
+    let mut next_input = 1;
+    if let Some(audio) = audio {
+        if audio.offset > 0.0 {
+            args.extend(["-itsoffset".into(), format!("{:.3}", audio.offset)]);
+        } else if audio.offset < 0.0 {
+            args.extend(["-ss".into(), format!("{:.3}", -audio.offset)]);
+        }
+        args.extend(["-i".into(), audio.path.display().to_string()]);
+        next_input += 1;
+    }
+
+    let soft_subtitles = subtitles.filter(|s| !s.burn);
+    if let Some(subtitles) = soft_subtitles {
+        args.extend(["-i".into(), subtitles.path.display().to_string()]);
+    }
+
+    args.extend(["-map".into(), "0:v".into()]);
+    match audio {
+        Some(audio) => {
+            args.extend(["-map".into(), "1:a".into()]);
+            if audio.volume != 1.0 {
+                args.extend(["-filter:a".into(), format!("volume={}", audio.volume)]);
+            }
+            let codec = if webm { "libopus" } else { "aac" };
+            args.extend(["-c:a".into(), codec.into()]);
+        }
+        // Keep any sound manim put in the video itself
+        None => args.extend(["-map".into(), "0:a?".into(), "-c:a".into(), "copy".into()]),
+    }
+
+    match subtitles {
+        Some(subtitles) if subtitles.burn => {
+            args.extend(["-vf".into(), format!("subtitles=filename='{}'", filter_escape(subtitles.path))]);
+            let codec = if webm { "libvpx-vp9" } else { "libx264" };
+            args.extend(["-c:v".into(), codec.into(), "-pix_fmt".into(), "yuv420p".into()]);
+        }
+        _ => args.extend(["-c:v".into(), "copy".into()]),
+    }
+
+    if soft_subtitles.is_some() {
+        args.extend(["-map".into(), format!("{}:s", next_input)]);
+        let codec = if webm { "webvtt" } else { "mov_text" };
+        args.extend(["-c:s".into(), codec.into()]);
+    }
+
+    args.push(output.display().to_string());
+    args
+}
+
+/// Escape a path for use inside a quoted filtergraph option
+fn filter_escape(path: &Path) -> String {
+    path.display()
+        .to_string()
+        .replace('\\', "\\\\")
+        .replace('\'', r"'\''")
+        .replace(':', "\\:")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xfade_filter() {
+        assert_eq!(
+            xfade_filter(&[4.0, 3.0, 5.0], 0.5, false),
+            "[0:v][1:v]xfade=transition=fade:duration=0.500:offset=3.500[x1];\
+             [x1][2:v]xfade=transition=fade:duration=0.500:offset=6.000[out]"
+        );
+        assert_eq!(xfade_filter(&[4.0], 0.5, false), "[0:v]null[out]");
+
+        assert_eq!(
+            xfade_filter(&[4.0, 3.0, 5.0], 0.5, true),
+            "[0:v][1:v]xfade=transition=fade:duration=0.500:offset=3.500[x1];\
+             [x1][2:v]xfade=transition=fade:duration=0.500:offset=6.000[out];\
+             [0:a][1:a]acrossfade=d=0.500[a1];[a1][2:a]acrossfade=d=0.500[aout]"
+        );
+    }
+
+    #[test]
+    fn test_mux_args_soft_subtitles_with_audio() {
+        let audio = AudioInput { path: Path::new("voice.mp3"), offset: 1.5, volume: 0.8 };
+        let subtitles = SubtitleInput { path: Path::new("captions.srt"), burn: false };
+
+        let args = mux_args(Path::new("in.mp4"), Some(&audio), Some(&subtitles), Path::new("out.mp4"));
+
+        assert_eq!(
+            args.join(" "),
+            "-y -loglevel error -i in.mp4 -itsoffset 1.500 -i voice.mp3 -i captions.srt \
+             -map 0:v -map 1:a -filter:a volume=0.8 -c:a aac -c:v copy \
+             -map 2:s -c:s mov_text out.mp4"
+        );
+    }
+
+    #[test]
+    fn test_mux_args_burned_subtitles() {
+        let audio = AudioInput { path: Path::new("voice.ogg"), offset: -2.0, volume: 1.0 };
+        let subtitles = SubtitleInput { path: Path::new("c:aption's.vtt"), burn: true };
+
+        let args = mux_args(Path::new("in.webm"), Some(&audio), Some(&subtitles), Path::new("out.webm"));
+
+        assert_eq!(
+            args.join(" "),
+            "-y -loglevel error -i in.webm -ss 2.000 -i voice.ogg -map 0:v -map 1:a -c:a libopus \
+             -vf subtitles=filename='c\\:aption'\\''s.vtt' -c:v libvpx-vp9 -pix_fmt yuv420p out.webm"
+        );
+    }
+}
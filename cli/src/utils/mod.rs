@@ -1,4 +1,9 @@
+pub mod ffmpeg;
+pub mod output;
+pub mod process;
 pub mod progress;
+pub mod sandbox;
+pub mod system;
 
 use std::path::Path;
 
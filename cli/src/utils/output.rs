@@ -0,0 +1,253 @@
+//! Output for scripts. With `--json` or `--quiet`, what commands print for
+//! people goes to stderr (or nowhere with `--quiet`), stdout carries only the
+//! command's result, and the exit status tells failures apart.
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::process::ExitCode;
+use std::sync::{Mutex, OnceLock};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mode {
+    /// Print the result as a JSON object on stdout
+    pub json: bool,
+    /// Print nothing but errors (and the JSON result with `--json`)
+    pub quiet: bool,
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+/// Stdout as it was before `init` pointed it elsewhere
+static STDOUT: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Set by commands with `set_result`
+static RESULT: Mutex<Option<Value>> = Mutex::new(None);
+
+/// Why a command failed, as `error.code` in JSON and as the exit status
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Failed,
+    /// The config files, variables or flags are invalid
+    Config,
+    /// Ollama, the marketplace or another service could not be reached
+    Unavailable,
+    /// Checks ran and some failed: doctor, validate, test, `render --check`
+    CheckFailed,
+    /// Manim failed to render the scene
+    RenderFailed,
+    Interrupted,
+}
+
+impl ErrorCode {
+    /// 2 is left to usage errors, which clap reports
+    pub fn exit_status(self) -> u8 {
+        match self {
+            ErrorCode::Failed => 1,
+            ErrorCode::Config => 3,
+            ErrorCode::Unavailable => 4,
+            ErrorCode::CheckFailed => 5,
+            ErrorCode::RenderFailed => 6,
+            ErrorCode::Interrupted => 130,
+        }
+    }
+}
+
+/// An error with a code other than `failed`. Shown like the error it wraps.
+#[derive(Debug)]
+pub struct Failure {
+    pub code: ErrorCode,
+    error: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl std::error::Error for Failure {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Give `error` a code
+pub fn with_code(code: ErrorCode, error: anyhow::Error) -> anyhow::Error {
+    anyhow::Error::new(Failure {
+        code,
+        error: error.into(),
+    })
+}
+
+/// A new error with a code
+pub fn failure(code: ErrorCode, message: impl Into<String>) -> anyhow::Error {
+    with_code(code, anyhow::anyhow!(message.into()))
+}
+
+fn classify(error: &anyhow::Error) -> ErrorCode {
+    for cause in error.chain() {
+        if let Some(failure) = cause.downcast_ref::<Failure>() {
+            return failure.code;
+        }
+        if let Some(termination) = cause.downcast_ref::<crate::commands::render::Termination>() {
+            return match termination {
+                crate::commands::render::Termination::Cancelled => ErrorCode::Interrupted,
+                _ => ErrorCode::Failed,
+            };
+        }
+        if cause.is::<reqwest::Error>() {
+            return ErrorCode::Unavailable;
+        }
+    }
+    ErrorCode::Failed
+}
+
+/// Set the mode from the global flags. Call before anything is printed.
+pub fn init(mode: Mode, no_color: bool) -> Result<()> {
+    MODE.set(mode).ok();
+
+    if no_color || mode.json || std::env::var_os("NO_COLOR").is_some() {
+        colored::control::set_override(false);
+    }
+    if mode.json || mode.quiet {
+        redirect_stdout(mode.quiet)?;
+    }
+    Ok(())
+}
+
+pub fn mode() -> Mode {
+    MODE.get().copied().unwrap_or_default()
+}
+
+pub fn json() -> bool {
+    mode().json
+}
+
+/// `--json` or `--quiet`: no logo, progress bars or prompts
+pub fn is_scripted() -> bool {
+    let mode = mode();
+    mode.json || mode.quiet
+}
+
+/// Point stdout at stderr, or at nothing when quiet, keeping the original
+/// for the result
+#[cfg(unix)]
+fn redirect_stdout(quiet: bool) -> Result<()> {
+    use std::os::fd::{AsRawFd, FromRawFd};
+
+    std::io::stdout().flush()?;
+    let null = File::options().write(true).open("/dev/null")?;
+    let target = if quiet { null.as_raw_fd() } else { libc::STDERR_FILENO };
+
+    // SAFETY: plain file descriptor calls; `saved` is owned by the File
+    unsafe {
+        let saved = libc::dup(libc::STDOUT_FILENO);
+        if saved < 0 || libc::dup2(target, libc::STDOUT_FILENO) < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        STDOUT.set(Mutex::new(File::from_raw_fd(saved))).ok();
+    }
+    Ok(())
+}
+
+/// Stdout stays as it is; the result is still printed last
+#[cfg(not(unix))]
+fn redirect_stdout(_quiet: bool) -> Result<()> {
+    Ok(())
+}
+
+/// Record what the command did, for `--json`. Later calls replace it.
+pub fn set_result(value: impl Serialize) {
+    let value = serde_json::to_value(value).unwrap_or(Value::Null);
+    *RESULT.lock().unwrap() = Some(value);
+}
+
+/// Add to the recorded result
+pub fn update_result(f: impl FnOnce(&mut Value)) {
+    if let Some(result) = RESULT.lock().unwrap().as_mut() {
+        f(result);
+    }
+}
+
+/// Remove the recorded result, e.g. to nest it in another
+pub fn take_result() -> Option<Value> {
+    RESULT.lock().unwrap().take()
+}
+
+/// Report the outcome of `command` and turn it into the exit status
+pub fn finish(command: &str, outcome: Result<()>) -> ExitCode {
+    let result = RESULT.lock().unwrap().take().unwrap_or(Value::Null);
+    let error = outcome.err();
+    let code = error.as_ref().map(classify);
+
+    if json() {
+        let report = Report {
+            ok: error.is_none(),
+            command,
+            result,
+            error: error.as_ref().zip(code).map(|(error, code)| {
+                json!({
+                    "code": code,
+                    "message": format!("{:#}", error),
+                })
+            }),
+        };
+        print_result(&report);
+    } else if let Some(error) = &error {
+        eprintln!("Error: {:?}", error);
+    }
+
+    match code {
+        None => ExitCode::SUCCESS,
+        Some(code) => ExitCode::from(code.exit_status()),
+    }
+}
+
+/// What `--json` prints, fields in this order
+#[derive(Serialize)]
+struct Report<'a> {
+    ok: bool,
+    command: &'a str,
+    result: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Value>,
+}
+
+fn print_result(report: &Report) {
+    let text = serde_json::to_string_pretty(report).unwrap_or_default();
+    match STDOUT.get() {
+        Some(stdout) => {
+            let mut stdout = stdout.lock().unwrap();
+            writeln!(stdout, "{}", text).ok();
+            stdout.flush().ok();
+        }
+        None => println!("{}", text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_errors_through_context() {
+        let error = failure(ErrorCode::CheckFailed, "2 checks failed");
+        let error = error.context("Doctor found problems");
+        assert_eq!(classify(&error), ErrorCode::CheckFailed);
+        assert_eq!(format!("{:#}", error), "Doctor found problems: 2 checks failed");
+
+        let error = with_code(
+            ErrorCode::Config,
+            anyhow::anyhow!("expected u32").context("Invalid thumbnails.width"),
+        );
+        assert_eq!(classify(&error), ErrorCode::Config);
+        assert_eq!(format!("{:#}", error), "Invalid thumbnails.width: expected u32");
+
+        assert_eq!(classify(&anyhow::anyhow!("nope")), ErrorCode::Failed);
+    }
+}
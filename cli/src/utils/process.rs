@@ -0,0 +1,132 @@
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Process groups of running child processes, killed together on cancellation
+static RUNNING: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Set once the user has asked to cancel (Ctrl-C)
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Run the child in its own process group with optional CPU-time (seconds) and
+/// address-space (MB) limits, so it and everything it spawns can be killed at once
+pub fn isolate(cmd: &mut Command, cpu_secs: Option<u64>, memory_mb: Option<u64>) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        cmd.process_group(0);
+
+        if cpu_secs.is_some() || memory_mb.is_some() {
+            // SAFETY: setrlimit is async-signal-safe and the closure captures only
+            // plain integers, so it is sound to run between fork and exec
+            unsafe {
+                cmd.pre_exec(move || {
+                    if let Some(secs) = cpu_secs {
+                        // SIGXCPU at the limit; SIGKILL a second later if it is ignored
+                        let limit = libc::rlimit {
+                            rlim_cur: secs as libc::rlim_t,
+                            rlim_max: secs.saturating_add(1) as libc::rlim_t,
+                        };
+                        check_os(libc::setrlimit(libc::RLIMIT_CPU, &limit))?;
+                    }
+                    if let Some(mb) = memory_mb {
+                        let bytes = mb.saturating_mul(1024 * 1024);
+                        check_os(libc::setrlimit(libc::RLIMIT_AS, &rlimit(bytes)))?;
+                    }
+                    Ok(())
+                });
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (cmd, cpu_secs, memory_mb);
+    }
+}
+
+#[cfg(unix)]
+fn rlimit(value: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    }
+}
+
+#[cfg(unix)]
+fn check_os(ret: libc::c_int) -> std::io::Result<()> {
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// CPU time (user + system) of all children of this process reaped so far
+pub fn children_cpu_time() -> Option<Duration> {
+    #[cfg(unix)]
+    {
+        let mut usage = std::mem::MaybeUninit::<libc::rusage>::zeroed();
+        // SAFETY: getrusage only writes into the rusage it is given
+        let usage = unsafe {
+            if libc::getrusage(libc::RUSAGE_CHILDREN, usage.as_mut_ptr()) != 0 {
+                return None;
+            }
+            usage.assume_init()
+        };
+        let time = |t: libc::timeval| {
+            Duration::from_secs(t.tv_sec as u64) + Duration::from_micros(t.tv_usec as u64)
+        };
+        Some(time(usage.ru_utime) + time(usage.ru_stime))
+    }
+
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+/// Kill a child started with [`isolate`] together with its whole process group
+pub fn kill_group(pid: u32) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .output();
+    }
+}
+
+/// Track a running child so that [`cancel_all`] can reach it
+pub fn register(pid: u32) {
+    RUNNING.lock().unwrap().push(pid);
+}
+
+pub fn unregister(pid: u32) {
+    RUNNING.lock().unwrap().retain(|p| *p != pid);
+}
+
+/// Kill every registered child. Returns false when nothing was running.
+pub fn cancel_all() -> bool {
+    let running = RUNNING.lock().unwrap();
+    if running.is_empty() {
+        return false;
+    }
+
+    CANCELLED.store(true, Ordering::SeqCst);
+    for pid in running.iter() {
+        kill_group(*pid);
+    }
+
+    true
+}
+
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::SeqCst)
+}
@@ -1,9 +1,26 @@
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use std::time::Duration;
 
+use crate::utils::output;
+
+/// Progress is drawn for people only, not with --json or --quiet
+pub fn hide_if_scripted(pb: &ProgressBar) {
+    if output::is_scripted() {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
+}
+
+/// Plain progress bar that follows the output mode
+pub fn new_bar(total: u64) -> ProgressBar {
+    let pb = ProgressBar::new(total);
+    hide_if_scripted(&pb);
+    pb
+}
+
 /// Create a spinner for indeterminate progress
 pub fn create_spinner(message: &str) -> ProgressBar {
     let pb = ProgressBar::new_spinner();
+    hide_if_scripted(&pb);
 
     pb.set_style(
         ProgressStyle::default_spinner()
@@ -20,7 +37,7 @@ pub fn create_spinner(message: &str) -> ProgressBar {
 
 /// Create a progress bar for determinate progress
 pub fn create_progress_bar(total: u64, message: &str) -> ProgressBar {
-    let pb = ProgressBar::new(total);
+    let pb = new_bar(total);
 
     pb.set_style(
         ProgressStyle::default_bar()
@@ -36,7 +53,7 @@ pub fn create_progress_bar(total: u64, message: &str) -> ProgressBar {
 
 /// Create a progress bar for file downloads
 pub fn create_download_bar(total: u64) -> ProgressBar {
-    let pb = ProgressBar::new(total);
+    let pb = new_bar(total);
 
     pb.set_style(
         ProgressStyle::default_bar()
@@ -58,7 +75,7 @@ pub struct MultiStepProgress {
 impl MultiStepProgress {
     pub fn new(steps: Vec<String>) -> Self {
         let total = steps.len() as u64;
-        let pb = ProgressBar::new(total);
+        let pb = new_bar(total);
 
         pb.set_style(
             ProgressStyle::default_bar()
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use std::path::Path;
+use std::process::Command;
+
+/// Build a command that runs `program` inside a bubblewrap sandbox.
+///
+/// The sandbox has no network, a private /tmp, and a read-only view of the
+/// filesystem except for the `writable` directories. `readable` directories are
+/// re-exposed read-only on top of the private /tmp in case they live there.
+/// All paths must be absolute and exist.
+pub fn command(
+    program: &str,
+    readable: &[&Path],
+    writable: &[&Path],
+    workdir: &Path,
+) -> Result<Command> {
+    check_available()?;
+
+    let mut cmd = Command::new("bwrap");
+    cmd.args(["--ro-bind", "/", "/"])
+        .args(["--dev", "/dev"])
+        .args(["--proc", "/proc"])
+        .args(["--tmpfs", "/tmp"]);
+
+    for dir in readable {
+        cmd.arg("--ro-bind").arg(dir).arg(dir);
+    }
+    for dir in writable {
+        cmd.arg("--bind").arg(dir).arg(dir);
+    }
+
+    cmd.arg("--chdir")
+        .arg(workdir)
+        .args(["--unshare-all", "--die-with-parent", "--new-session", "--"])
+        .arg(program);
+
+    Ok(cmd)
+}
+
+/// Check that sandboxing is supported here and bubblewrap is installed
+pub fn check_available() -> Result<()> {
+    if !cfg!(target_os = "linux") {
+        anyhow::bail!("Sandboxed rendering is only supported on Linux");
+    }
+
+    if Command::new("bwrap").arg("--version").output().is_err() {
+        anyhow::bail!(
+            "Sandboxed rendering needs bubblewrap. Install it with your package manager, e.g.\n\
+             apt install bubblewrap   or   dnf install bubblewrap"
+        );
+    }
+
+    Ok(())
+}
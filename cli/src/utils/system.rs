@@ -0,0 +1,75 @@
+use std::process::Command;
+
+/// An external program AnimaForge relies on
+pub struct Tool {
+    pub name: &'static str,
+    pub program: &'static str,
+    pub version_arg: &'static str,
+    /// Rendering cannot work without it
+    pub required: bool,
+    pub install: &'static str,
+}
+
+pub const MANIM: Tool = Tool {
+    name: "manim",
+    program: "manim",
+    version_arg: "--version",
+    required: true,
+    install: "pip install manim",
+};
+
+pub const FFMPEG: Tool = Tool {
+    name: "ffmpeg",
+    program: "ffmpeg",
+    version_arg: "-version",
+    required: true,
+    install: "install it from https://ffmpeg.org/download.html",
+};
+
+pub const LATEX: Tool = Tool {
+    name: "LaTeX",
+    program: "latex",
+    version_arg: "--version",
+    required: false,
+    install: "install TeX Live or MiKTeX (needed for Tex and MathTex)",
+};
+
+pub const PYTHON: Tool = Tool {
+    name: "Python",
+    program: if cfg!(windows) { "python" } else { "python3" },
+    version_arg: "--version",
+    required: true,
+    install: "install Python 3.8 or newer from https://www.python.org/downloads/",
+};
+
+/// Tools needed to render, in the order they are checked
+pub const RENDER_TOOLS: [Tool; 3] = [MANIM, FFMPEG, LATEX];
+
+impl Tool {
+    /// First line of the tool's version output, or `None` if it is not installed
+    pub fn version(&self) -> Option<String> {
+        let output = Command::new(self.program)
+            .arg(self.version_arg)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let text = if output.stdout.is_empty() {
+            &output.stderr
+        } else {
+            &output.stdout
+        };
+        let version = String::from_utf8_lossy(text)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or(self.program)
+            .split(" Copyright")
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Some(version)
+    }
+}
@@ -0,0 +1,268 @@
+"""
+JSON-over-stdio protocol between the AnimaForge CLI and the engine.
+
+Run with ``python -m animaforge_engine.protocol``. The engine reads one JSON
+request per line on stdin and answers with one JSON message per line on
+stdout, until stdin is closed:
+
+    -> {"id": 1, "method": "render", "params": {"file": "scene.py", ...}}
+    <- {"type": "ready", "protocol": 1, "version": "0.1.0"}
+    <- {"id": 1, "type": "progress", "stage": "rendering", "message": "..."}
+    <- {"id": 1, "type": "output", "stream": "stderr", "line": "..."}
+    <- {"id": 1, "type": "result", "result": {"path": "..."}}
+    <- {"id": 1, "type": "error", "error": {"code": "...", "message": "..."}}
+
+Every request ends with exactly one ``result`` or ``error`` message. Anything
+else the engine prints goes to stderr so stdout only ever carries messages.
+"""
+
+import json
+import sys
+import threading
+from pathlib import Path
+from typing import Any, Callable, Optional
+
+from . import __version__
+from .renderer import run_manim, render_preview, render_batch, get_render_info
+from .templates import get_template, list_templates
+from .validator import validate_and_report, validate_manim_code, extract_scene_class_name
+
+PROTOCOL_VERSION = 1
+
+
+class ProtocolError(Exception):
+    """An error reported to the client as a structured ``error`` message."""
+
+    def __init__(self, code: str, message: str, details: Optional[dict] = None):
+        super().__init__(message)
+        self.code = code
+        self.message = message
+        self.details = details or {}
+
+    def to_dict(self) -> dict:
+        error = {"code": self.code, "message": self.message}
+        if self.details:
+            error["details"] = self.details
+        return error
+
+
+class Connection:
+    """Writes protocol messages to the real stdout, one JSON object per line."""
+
+    def __init__(self, stream):
+        self.stream = stream
+        self.lock = threading.Lock()
+
+    def send(self, message: dict) -> None:
+        with self.lock:
+            self.stream.write(json.dumps(message) + "\n")
+            self.stream.flush()
+
+
+class Request:
+    """A single request and the channel to report on it."""
+
+    def __init__(self, connection: Connection, request_id: Any, params: dict):
+        self.connection = connection
+        self.id = request_id
+        self.params = params
+
+    def progress(self, stage: str, message: Optional[str] = None, **fields: Any) -> None:
+        event = {"id": self.id, "type": "progress", "stage": stage, **fields}
+        if message:
+            event["message"] = message
+        self.connection.send(event)
+
+    def started(self, pid: int) -> None:
+        """Report the process group doing the work so the client can kill it."""
+        self.progress("started", pid=pid)
+
+    def output(self, stream: str, line: str) -> None:
+        self.connection.send({"id": self.id, "type": "output", "stream": stream, "line": line})
+
+    def param(self, name: str, default: Any = ..., kind: type = str) -> Any:
+        if name not in self.params:
+            if default is ...:
+                raise ProtocolError("invalid_params", f"Missing parameter: {name}")
+            return default
+
+        value = self.params[name]
+        if value is not None and not isinstance(value, kind):
+            raise ProtocolError(
+                "invalid_params", f"Parameter '{name}' must be of type {kind.__name__}"
+            )
+        return value
+
+
+def _read_code(request: Request) -> str:
+    """Source code from the ``code`` or ``file`` parameter."""
+    if "code" in request.params:
+        return request.param("code")
+
+    path = Path(request.param("file"))
+    try:
+        return path.read_text()
+    except OSError as e:
+        raise ProtocolError("file_not_found", f"Cannot read {path}: {e.strerror}")
+
+
+class RenderJob:
+    """A validated ``render`` request."""
+
+    def __init__(self, request: Request):
+        self.file = Path(request.param("file"))
+        self.media_dir = Path(request.param("media_dir"))
+        self.flags = [str(flag) for flag in request.param("args", [], list)]
+        self.output_name = request.param("output_name", None)
+        self.scene = request.param("scene", None)
+        self.limits = request.param("limits", {}, dict)
+        # Manim reads manim.cfg and relative asset paths from the client's directory
+        self.cwd = request.param("cwd", None)
+
+        code = _read_code(request)
+        request.progress("validating", "Validating animation code...")
+        is_valid, error = validate_manim_code(code)
+        if not is_valid:
+            raise ProtocolError("invalid_code", error)
+
+        self.scene = self.scene or extract_scene_class_name(code)
+        if not self.scene:
+            raise ProtocolError("invalid_code", "Could not extract Scene class name from code")
+
+        self.media_dir.mkdir(parents=True, exist_ok=True)
+        request.progress("rendering", f"Rendering scene: {self.scene}")
+
+
+def render_failed(error: RuntimeError) -> ProtocolError:
+    """Report a failed render with manim's exit code and CPU time when known."""
+    details = {}
+    for field in ("returncode", "cpu_secs"):
+        value = getattr(error, field, None)
+        if value is not None:
+            details[field] = value
+    return ProtocolError("render_failed", str(error).split("\n")[0], details)
+
+
+def handle_render(request: Request) -> dict:
+    """Render one scene of a file with the given manim flags."""
+    job = RenderJob(request)
+    try:
+        path = run_manim(
+            job.file,
+            job.scene,
+            job.media_dir,
+            job.flags,
+            output_name=job.output_name,
+            on_output=request.output,
+            cwd=job.cwd,
+        )
+    except RuntimeError as e:
+        raise render_failed(e)
+
+    return {"path": path, "scene": job.scene}
+
+
+def handle_preview(request: Request) -> dict:
+    code = _read_code(request)
+    try:
+        path = render_preview(code, request.param("output_path"), show=False)
+    except ValueError as e:
+        raise ProtocolError("invalid_code", str(e))
+    except RuntimeError as e:
+        raise ProtocolError("render_failed", str(e).split("\n")[0])
+    return {"path": path}
+
+
+def handle_batch(request: Request) -> dict:
+    codes = request.param("codes", kind=list)
+    quality = request.param("quality", "medium")
+    paths = render_batch(codes, request.param("output_path"), quality)
+    return {"paths": paths}
+
+
+def handle_info(request: Request) -> dict:
+    return get_render_info(request.param("output_path"))
+
+
+def handle_validate(request: Request) -> dict:
+    return validate_and_report(_read_code(request))
+
+
+def handle_template(request: Request) -> dict:
+    name = request.param("name")
+    values = request.param("values", {}, dict)
+    try:
+        return {"name": name, "code": get_template(name, **values)}
+    except ValueError as e:
+        raise ProtocolError("unknown_template", str(e), {"available": list_templates()})
+
+
+def handle_templates(request: Request) -> dict:
+    return {"templates": list_templates()}
+
+
+HANDLERS: dict[str, Callable[[Request], dict]] = {
+    "render": handle_render,
+    "preview": handle_preview,
+    "batch": handle_batch,
+    "info": handle_info,
+    "validate": handle_validate,
+    "template": handle_template,
+    "templates": handle_templates,
+}
+
+
+def handle_line(
+    connection: Connection, line: str, handlers: dict[str, Callable[[Request], dict]] = HANDLERS
+) -> None:
+    """Parse and answer one request line."""
+    request_id = None
+    try:
+        try:
+            message = json.loads(line)
+        except json.JSONDecodeError as e:
+            raise ProtocolError("invalid_request", f"Request is not valid JSON: {e}")
+
+        if not isinstance(message, dict):
+            raise ProtocolError("invalid_request", "Request must be a JSON object")
+
+        request_id = message.get("id")
+        method = message.get("method")
+        handler = handlers.get(method)
+        if handler is None:
+            raise ProtocolError(
+                "unknown_method", f"Unknown method: {method}", {"methods": sorted(handlers)}
+            )
+
+        params = message.get("params") or {}
+        if not isinstance(params, dict):
+            raise ProtocolError("invalid_request", "params must be a JSON object")
+
+        result = handler(Request(connection, request_id, params))
+        connection.send({"id": request_id, "type": "result", "result": result})
+
+    except ProtocolError as e:
+        connection.send({"id": request_id, "type": "error", "error": e.to_dict()})
+    except Exception as e:
+        error = ProtocolError("internal", str(e), {"exception": type(e).__name__})
+        connection.send({"id": request_id, "type": "error", "error": error.to_dict()})
+
+
+def serve(stdin=None, stdout=None) -> None:
+    """Answer requests from stdin until it is closed."""
+    stdin = stdin or sys.stdin
+    connection = Connection(stdout or sys.stdout)
+
+    # Engine functions print progress; keep that off the message channel
+    original_stdout, sys.stdout = sys.stdout, sys.stderr
+    try:
+        connection.send({"type": "ready", "protocol": PROTOCOL_VERSION, "version": __version__})
+        for line in stdin:
+            if line.strip():
+                handle_line(connection, line)
+    finally:
+        sys.stdout = original_stdout
+
+
+if __name__ == "__main__":
+    serve()
@@ -1,17 +1,31 @@
 """Renderer for executing and rendering Manim animations."""
 
 import os
+import resource
 import subprocess
 import tempfile
+import threading
 import shutil
 from pathlib import Path
-from typing import Optional, Literal
+from typing import Callable, Optional, Literal
 
 from .validator import validate_manim_code, extract_scene_class_name
 
 QualityLevel = Literal["low", "medium", "high", "4k"]
 
 
+class RenderError(RuntimeError):
+    """Manim exited unsuccessfully; ``returncode`` is negative when killed by a signal.
+    ``cpu_secs`` is the CPU time manim used, when known."""
+
+    def __init__(
+        self, message: str, returncode: Optional[int] = None, cpu_secs: Optional[float] = None
+    ):
+        super().__init__(message)
+        self.returncode = returncode
+        self.cpu_secs = cpu_secs
+
+
 def render_animation(
     code: str,
     output_path: str,
@@ -89,95 +103,157 @@ def render_animation(
         with open(script_file, "w") as f:
             f.write(code)
 
-        # Build manim command
-        cmd = [
-            "manim",
-            quality_flags[quality],
-            str(script_file),
-            scene_name,
-            "--output_file",
-            scene_name,
-            "--media_dir",
-            str(output_dir),
-        ]
+        # Build manim flags
+        flags = [quality_flags[quality]]
 
         # Add optional parameters
         if format and format != "mp4":
-            cmd.extend(["--format", format])
+            flags.extend(["--format", format])
 
         if background_color:
-            cmd.extend(["--background_color", background_color])
+            flags.extend(["--background_color", background_color])
 
         if transparent:
-            cmd.append("--transparent")
+            flags.append("--transparent")
 
         if fps != 60:
-            cmd.extend(["--frame_rate", str(fps)])
+            flags.extend(["--frame_rate", str(fps)])
+
+        return run_manim(script_file, scene_name, output_dir, flags, timeout=300)
+
+
+def run_manim(
+    script_file: Path,
+    scene_name: str,
+    output_dir: Path,
+    flags: list[str],
+    output_name: Optional[str] = None,
+    timeout: Optional[float] = None,
+    on_output: Optional[Callable[[str, str], None]] = None,
+    on_start: Optional[Callable[[int], None]] = None,
+    limits: Optional[dict] = None,
+    cwd: Optional[str] = None,
+) -> str:
+    """
+    Run manim on a script file and locate the rendered output.
 
-        # Execute manim command
-        try:
-            print(f"Rendering animation with command: {' '.join(cmd)}")
-
-            result = subprocess.run(
-                cmd,
-                capture_output=True,
-                text=True,
-                timeout=300,  # 5 minute timeout
-                check=True,
-            )
-
-            print("Manim output:", result.stdout)
-
-            # Find the output file
-            # Manim creates a structure like: output_dir/videos/animation/quality/SceneName.mp4
-            video_dir = output_dir / "videos" / "animation" / quality
-
-            if not video_dir.exists():
-                # Try alternate structure: output_dir/videos/quality/
-                video_dir = output_dir / "videos" / quality
-
-            # Look for the video file
-            possible_extensions = [".mp4", ".mov", ".gif", ".png"]
-            output_file = None
-
-            for ext in possible_extensions:
-                candidate = video_dir / f"{scene_name}{ext}"
-                if candidate.exists():
-                    output_file = candidate
-                    break
-
-            # Also check in the direct output directory
-            if not output_file:
-                for ext in possible_extensions:
-                    candidate = output_dir / f"{scene_name}{ext}"
-                    if candidate.exists():
-                        output_file = candidate
-                        break
-
-            if not output_file:
-                # Search recursively
-                for file in output_dir.rglob(f"{scene_name}.*"):
-                    if file.suffix in possible_extensions:
-                        output_file = file
-                        break
-
-            if not output_file or not output_file.exists():
-                raise RuntimeError(
-                    f"Rendering completed but output file not found. "
-                    f"Expected in {video_dir} or {output_dir}"
-                )
-
-            return str(output_file.absolute())
-
-        except subprocess.TimeoutExpired:
-            raise RuntimeError("Rendering timed out after 5 minutes")
-
-        except subprocess.CalledProcessError as e:
-            error_msg = f"Manim rendering failed:\nSTDOUT: {e.stdout}\nSTDERR: {e.stderr}"
-            raise RuntimeError(error_msg)
+    Args:
+        script_file: Python file containing the scene
+        scene_name: Scene class to render
+        output_dir: Manim media directory
+        flags: Extra manim flags, e.g. a quality flag
+        output_name: Output file name (default: the scene name)
+        timeout: Seconds before the render is aborted (default: no limit)
+        on_output: Called with ("stdout" | "stderr", line) for every line manim prints
+        on_start: Called with the manim process id. Manim then runs in its own
+            process group so the caller can kill it and its children together.
+        limits: Optional "cpu_secs" and "memory_mb" resource limits for manim
+        cwd: Directory to run manim in (default: the current one)
 
-        except Exception as e:
-            raise RuntimeError(f"Rendering failed: {str(e)}")
+    Returns:
+        Path to the rendered file
+
+    Raises:
+        RenderError: If manim fails or times out
+        RuntimeError: If manim cannot be started or produces no output
+    """
+    output_name = output_name or scene_name
+    cmd = [
+        "manim",
+        *flags,
+        str(script_file),
+        scene_name,
+        "--output_file",
+        output_name,
+        "--media_dir",
+        str(output_dir),
+    ]
+
+    print(f"Rendering animation with command: {' '.join(cmd)}")
+
+    try:
+        # Text mode splits tqdm's carriage-return redraws into separate lines
+        process = subprocess.Popen(
+            cmd,
+            stdout=subprocess.PIPE,
+            stderr=subprocess.PIPE,
+            text=True,
+            cwd=cwd,
+            start_new_session=on_start is not None,
+            preexec_fn=(lambda: apply_limits(limits)) if limits else None,
+        )
+    except OSError as e:
+        raise RuntimeError(f"Rendering failed: {str(e)}")
+
+    if on_start:
+        on_start(process.pid)
+
+    captured = {"stdout": [], "stderr": []}
+
+    def drain(stream, name):
+        for line in stream:
+            line = line.rstrip("\n")
+            captured[name].append(line)
+            if on_output:
+                on_output(name, line)
+
+    readers = [
+        threading.Thread(target=drain, args=(process.stdout, "stdout"), daemon=True),
+        threading.Thread(target=drain, args=(process.stderr, "stderr"), daemon=True),
+    ]
+    for reader in readers:
+        reader.start()
+
+    try:
+        process.wait(timeout=timeout)
+    except subprocess.TimeoutExpired:
+        process.kill()
+        process.wait()
+        raise RenderError(f"Rendering timed out after {timeout:g} seconds", process.returncode)
+    finally:
+        for reader in readers:
+            reader.join()
+
+    if process.returncode != 0:
+        raise RenderError(
+            "Manim rendering failed:\n"
+            f"STDOUT: {chr(10).join(captured['stdout'])}\n"
+            f"STDERR: {chr(10).join(captured['stderr'])}",
+            process.returncode,
+        )
+
+    output_file = find_output(output_dir, Path(output_name).stem)
+    if not output_file:
+        raise RuntimeError(
+            f"Rendering completed but output file not found in {output_dir}"
+        )
+
+    return str(output_file.absolute())
+
+
+def apply_limits(limits: dict) -> None:
+    """Apply "cpu_secs" (CPU time) and "memory_mb" (address space) limits to this process."""
+    if limits.get("cpu_secs"):
+        secs = int(limits["cpu_secs"])
+        # SIGXCPU at the limit; SIGKILL a second later if it is ignored
+        resource.setrlimit(resource.RLIMIT_CPU, (secs, secs + 1))
+    if limits.get("memory_mb"):
+        size = int(limits["memory_mb"]) * 1024 * 1024
+        resource.setrlimit(resource.RLIMIT_AS, (size, size))
+
+
+def find_output(output_dir: Path, stem: str) -> Optional[Path]:
+    """Find a rendered file named `stem` anywhere under a manim media directory."""
+    possible_extensions = [".mp4", ".mov", ".gif", ".png", ".webm"]
+
+    for file in sorted(output_dir.rglob(f"{stem}.*")):
+        # Skip manim's intermediate partial movie files
+        if "partial_movie_files" in file.parts:
+            continue
+        if file.suffix in possible_extensions:
+            return file
+
+    return None
 
 
 def render_preview(
@@ -0,0 +1,331 @@
+"""
+Long-lived engine worker for the AnimaForge CLI.
+
+Run with ``python -m animaforge_engine.worker --socket PATH``. The worker
+imports manim once, then serves the same line protocol as
+``animaforge_engine.protocol`` to any number of clients on a Unix socket.
+Each render runs in a forked child of the warm interpreter, so it starts
+without paying manim's import cost again.
+
+On top of the protocol methods the worker answers ``ping`` (health check)
+and ``shutdown``. It exits on its own after ``--idle-timeout`` seconds
+without clients.
+"""
+
+import argparse
+import os
+import signal
+import socket
+import sys
+import threading
+import time
+import traceback
+from pathlib import Path
+from typing import Callable, Optional
+
+from . import __version__
+from .protocol import (
+    HANDLERS,
+    PROTOCOL_VERSION,
+    Connection,
+    RenderJob,
+    Request,
+    handle_line,
+    render_failed,
+)
+from .renderer import RenderError, apply_limits, find_output, run_manim
+
+try:
+    # Importing manim up front is the point of the worker
+    import manim  # noqa: F401
+
+    MANIM_LOADED = True
+except Exception:
+    MANIM_LOADED = False
+
+DEFAULT_IDLE_TIMEOUT = 600
+
+# Fork only while no other thread is in the middle of starting a render
+FORK_LOCK = threading.Lock()
+
+
+def fork_render(
+    job: RenderJob,
+    on_start: Callable[[int], None],
+    on_output: Callable[[str, str], None],
+) -> str:
+    """Render a job in a forked child running manim's own command line."""
+    output_name = job.output_name or job.scene
+    args = [
+        "render",
+        *job.flags,
+        str(job.file),
+        job.scene,
+        "-o",
+        output_name,
+        "--media_dir",
+        str(job.media_dir),
+    ]
+
+    out_r, out_w = os.pipe()
+    err_r, err_w = os.pipe()
+    with FORK_LOCK:
+        pid = os.fork()
+    if pid == 0:
+        os.close(out_r)
+        os.close(err_r)
+        _render_child(args, job.limits, job.cwd, out_w, err_w)
+
+    os.close(out_w)
+    os.close(err_w)
+    on_start(pid)
+
+    readers = [
+        threading.Thread(target=_forward, args=(out_r, "stdout", on_output, pid), daemon=True),
+        threading.Thread(target=_forward, args=(err_r, "stderr", on_output, pid), daemon=True),
+    ]
+    for reader in readers:
+        reader.start()
+
+    _, status, usage = os.wait4(pid, 0)
+    for reader in readers:
+        reader.join()
+
+    returncode = os.waitstatus_to_exitcode(status)
+    if returncode != 0:
+        raise RenderError(
+            f"Manim rendering failed with exit code {returncode}",
+            returncode,
+            cpu_secs=usage.ru_utime + usage.ru_stime,
+        )
+
+    output_file = find_output(job.media_dir, Path(output_name).stem)
+    if not output_file:
+        raise RuntimeError(f"Rendering completed but output file not found in {job.media_dir}")
+
+    return str(output_file.absolute())
+
+
+def _render_child(
+    args: list[str], limits: dict, cwd: Optional[str], out_w: int, err_w: int
+) -> None:
+    """Body of the forked render process. Never returns."""
+    code = 1
+    try:
+        # Own process group, so the client can kill the render and its children
+        os.setpgid(0, 0)
+        signal.signal(signal.SIGTERM, signal.SIG_DFL)
+
+        os.dup2(out_w, 1)
+        os.dup2(err_w, 2)
+        sys.stdout = sys.__stdout__
+        sys.stderr = sys.__stderr__
+        sys.stdout.reconfigure(line_buffering=True)
+
+        apply_limits(limits)
+
+        # Render from the client's directory, with its manim.cfg rather than
+        # the one manim read when the worker imported it
+        if cwd:
+            os.chdir(cwd)
+        from manim import config
+        from manim._config.utils import make_config_parser
+
+        config.digest_parser(make_config_parser())
+
+        from manim.__main__ import main
+
+        main(args=args, prog_name="manim", standalone_mode=False)
+        code = 0
+    except SystemExit as e:
+        code = e.code if isinstance(e.code, int) else (0 if e.code is None else 1)
+    except BaseException:
+        traceback.print_exc()
+        code = 1
+    finally:
+        try:
+            sys.stdout.flush()
+            sys.stderr.flush()
+        finally:
+            os._exit(code)
+
+
+def _forward(fd: int, stream: str, on_output: Callable[[str, str], None], pid: int) -> None:
+    """Pass every line from a render pipe to the client; stop the render if it went away."""
+    with os.fdopen(fd, "r", errors="replace") as pipe:
+        for line in pipe:
+            try:
+                on_output(stream, line.rstrip("\n"))
+            except OSError:
+                try:
+                    os.killpg(pid, signal.SIGKILL)
+                except OSError:
+                    pass
+                return
+
+
+class Worker:
+    """Accepts clients on a Unix socket until idle or told to shut down."""
+
+    def __init__(self, path: Path, idle_timeout: float, pid_file: Optional[Path] = None):
+        self.path = path
+        self.idle_timeout = idle_timeout
+        self.pid_file = pid_file
+        self.started_at = time.monotonic()
+        self.last_activity = self.started_at
+        self.clients = 0
+        self.renders = 0
+        self.lock = threading.Lock()
+        self.stopping = threading.Event()
+        self.handlers = {
+            **HANDLERS,
+            "render": self.render,
+            "ping": self.ping,
+            "shutdown": self.shutdown,
+        }
+
+    def render(self, request: Request) -> dict:
+        with self.lock:
+            self.renders += 1
+
+        job = RenderJob(request)
+        try:
+            if MANIM_LOADED:
+                path = fork_render(job, request.started, request.output)
+            else:
+                # manim could not be imported here; run it as a process instead
+                path = run_manim(
+                    job.file,
+                    job.scene,
+                    job.media_dir,
+                    job.flags,
+                    output_name=job.output_name,
+                    on_output=request.output,
+                    on_start=request.started,
+                    limits=job.limits,
+                    cwd=job.cwd,
+                )
+        except RuntimeError as e:
+            raise render_failed(e)
+
+        return {"path": path, "scene": job.scene}
+
+    def ping(self, request: Request) -> dict:
+        with self.lock:
+            return {
+                "pid": os.getpid(),
+                "version": __version__,
+                "uptime": round(time.monotonic() - self.started_at, 1),
+                "manim": MANIM_LOADED,
+                "clients": self.clients,
+                "renders": self.renders,
+            }
+
+    def shutdown(self, request: Request) -> dict:
+        self.stopping.set()
+        return {"pid": os.getpid()}
+
+    def idle(self) -> bool:
+        with self.lock:
+            return self.clients == 0 and time.monotonic() - self.last_activity > self.idle_timeout
+
+    def serve(self) -> None:
+        if self.path.exists():
+            self.path.unlink()
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+
+        server = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+        server.bind(str(self.path))
+        os.chmod(self.path, 0o600)
+        server.listen()
+        server.settimeout(1.0)
+        if self.pid_file:
+            self.pid_file.write_text(str(os.getpid()))
+
+        try:
+            while not self.stopping.is_set() and not self.idle():
+                try:
+                    client, _ = server.accept()
+                except socket.timeout:
+                    continue
+                client.settimeout(None)
+                with self.lock:
+                    self.clients += 1
+                threading.Thread(target=self.handle_client, args=(client,), daemon=True).start()
+        finally:
+            server.close()
+            # A newer worker may already own the path
+            try:
+                if self.path.exists() and not _socket_alive(self.path):
+                    self.path.unlink()
+            except OSError:
+                pass
+            # Left behind, the pid could later be reused by an unrelated process
+            try:
+                if self.pid_file and self.pid_file.read_text().strip() == str(os.getpid()):
+                    self.pid_file.unlink()
+            except OSError:
+                pass
+
+    def handle_client(self, client: socket.socket) -> None:
+        try:
+            reader = client.makefile("r", encoding="utf-8")
+            writer = client.makefile("w", encoding="utf-8")
+            connection = Connection(writer)
+            connection.send(
+                {
+                    "type": "ready",
+                    "protocol": PROTOCOL_VERSION,
+                    "version": __version__,
+                    "pid": os.getpid(),
+                }
+            )
+            for line in reader:
+                if line.strip():
+                    handle_line(connection, line, self.handlers)
+                if self.stopping.is_set():
+                    break
+        except OSError:
+            # The client went away
+            pass
+        finally:
+            client.close()
+            with self.lock:
+                self.clients -= 1
+                self.last_activity = time.monotonic()
+
+
+def _socket_alive(path: Path) -> bool:
+    probe = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+    try:
+        probe.connect(str(path))
+        return True
+    except OSError:
+        return False
+    finally:
+        probe.close()
+
+
+def main(argv: Optional[list[str]] = None) -> int:
+    parser = argparse.ArgumentParser(description="AnimaForge engine worker")
+    parser.add_argument("--socket", required=True, help="Unix socket path to listen on")
+    parser.add_argument(
+        "--idle-timeout",
+        type=float,
+        default=DEFAULT_IDLE_TIMEOUT,
+        help="Exit after this many seconds without clients",
+    )
+    parser.add_argument("--pid-file", help="File to hold the worker's pid while it runs")
+    args = parser.parse_args(argv)
+
+    # Keep stray prints away from whatever stdout the worker was started with
+    sys.stdout = sys.stderr
+    print(f"Engine worker {os.getpid()} listening on {args.socket} (manim loaded: {MANIM_LOADED})")
+
+    pid_file = Path(args.pid_file) if args.pid_file else None
+    Worker(Path(args.socket), args.idle_timeout, pid_file).serve()
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
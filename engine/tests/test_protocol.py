@@ -0,0 +1,104 @@
+"""Tests for the JSON-over-stdio protocol."""
+
+import io
+import json
+import os
+import sys
+import types
+
+from animaforge_engine import protocol, worker
+from animaforge_engine.protocol import Connection, RenderJob, Request, handle_line, serve
+
+SCENE = "from manim import *\nclass T(Scene):\n    def construct(self):\n        self.play(Create(Circle()))\n"
+
+
+def run(*requests):
+    """Serve the given request lines and return the decoded messages."""
+    stdin = io.StringIO("".join(line + "\n" for line in requests))
+    stdout = io.StringIO()
+    serve(stdin, stdout)
+    return [json.loads(line) for line in stdout.getvalue().splitlines()]
+
+
+class TestProtocol:
+    """Test request handling and message framing."""
+
+    def test_ready_handshake(self):
+        """The first message announces the protocol version."""
+        messages = run()
+        assert messages == [{"type": "ready", "protocol": 1, "version": "0.1.0"}]
+
+    def test_validate_result(self):
+        """Validation reports come back as a result for the request id."""
+        messages = run(json.dumps({"id": 7, "method": "validate", "params": {"code": SCENE}}))
+
+        result = messages[-1]
+        assert result["id"] == 7
+        assert result["type"] == "result"
+        assert result["result"]["valid"] is True
+        assert result["result"]["scene_class"] == "T"
+
+    def test_unknown_template_error(self):
+        """Engine errors become structured error messages."""
+        messages = run(json.dumps({"id": 1, "method": "template", "params": {"name": "nope"}}))
+
+        error = messages[-1]
+        assert error["type"] == "error"
+        assert error["error"]["code"] == "unknown_template"
+        assert "simple_text" in error["error"]["details"]["available"]
+
+    def test_invalid_requests(self):
+        """Malformed lines and unknown methods are answered, not fatal."""
+        stdout = io.StringIO()
+        connection = Connection(stdout)
+
+        handle_line(connection, "not json")
+        handle_line(connection, json.dumps({"id": 2, "method": "explode"}))
+        handle_line(connection, json.dumps({"id": 3, "method": "template", "params": {}}))
+
+        codes = [json.loads(line)["error"]["code"] for line in stdout.getvalue().splitlines()]
+        assert codes == ["invalid_request", "unknown_method", "invalid_params"]
+
+    def test_render_runs_in_client_directory(self, tmp_path, monkeypatch):
+        """Renders run manim from the directory the request names."""
+        (tmp_path / "scene.py").write_text(SCENE)
+        calls = []
+        monkeypatch.setattr(protocol, "run_manim", lambda *a, **kw: calls.append(kw) or "out.mp4")
+
+        params = {
+            "file": str(tmp_path / "scene.py"),
+            "media_dir": str(tmp_path / "media"),
+            "cwd": str(tmp_path),
+        }
+        messages = run(json.dumps({"id": 4, "method": "render", "params": params}))
+
+        assert messages[-1]["result"] == {"path": "out.mp4", "scene": "T"}
+        assert calls[0]["cwd"] == str(tmp_path)
+
+    def test_worker_render_reads_client_config(self, tmp_path, monkeypatch):
+        """Forked renders change to the client's directory and re-read manim.cfg there."""
+        (tmp_path / "scene.py").write_text(SCENE)
+        (tmp_path / "manim.cfg").write_text("[CLI]\n")
+
+        # Stand-ins for the manim modules the render child imports
+        config = types.SimpleNamespace(digest_parser=lambda parser: print("config", parser))
+        utils = types.SimpleNamespace(make_config_parser=lambda: os.path.exists("manim.cfg"))
+        main = types.SimpleNamespace(main=lambda **kw: print("cwd", os.getcwd()))
+        monkeypatch.setitem(sys.modules, "manim", types.SimpleNamespace(config=config))
+        monkeypatch.setitem(sys.modules, "manim._config.utils", utils)
+        monkeypatch.setitem(sys.modules, "manim.__main__", main)
+
+        params = {
+            "file": str(tmp_path / "scene.py"),
+            "media_dir": str(tmp_path / "media"),
+            "cwd": str(tmp_path),
+        }
+        job = RenderJob(Request(Connection(io.StringIO()), 1, params))
+        lines = []
+        try:
+            worker.fork_render(job, lambda pid: None, lambda stream, line: lines.append(line))
+        except RuntimeError:
+            # The stand-in renders nothing
+            pass
+
+        assert lines == ["config True", f"cwd {tmp_path}"]